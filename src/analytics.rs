@@ -0,0 +1,186 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use crate::recorder::{RecordFilter, TradeRecorder};
+use crate::types::TradeDetails;
+
+// 单个代币上的持仓轮次：从首次买入到持仓归零
+#[derive(Debug, Default)]
+struct OpenRound {
+    opened_at: i64,
+    raw_amount: u64,
+}
+
+// 根据记录的成交推导出的钱包行为特征
+#[derive(Debug, Default)]
+pub struct WalletReport {
+    pub trades: usize,
+    pub buys: usize,
+    pub sells: usize,
+    // 每笔买入花费的SOL(lamports)，已排序
+    pub buy_sizes: Vec<u64>,
+    // 已平仓轮次的持有时长(秒)，已排序
+    pub hold_durations: Vec<i64>,
+    pub open_positions: usize,
+    pub dex_counts: Vec<(String, usize)>,
+    // UTC小时分布
+    pub hour_histogram: [usize; 24],
+}
+
+impl WalletReport {
+    // trades需按时间从旧到新排列
+    pub fn from_trades(trades: &[TradeDetails]) -> Self {
+        let wsol = spl_token::native_mint::id();
+        let mut report = WalletReport::default();
+        let mut rounds: HashMap<Pubkey, OpenRound> = HashMap::new();
+        let mut dexes: HashMap<String, usize> = HashMap::new();
+
+        for trade in trades {
+            report.trades += 1;
+            *dexes.entry(trade.dex.to_string()).or_default() += 1;
+            let hour = trade.timestamp.rem_euclid(86_400) / 3_600;
+            report.hour_histogram[hour as usize] += 1;
+
+            if trade.input_token == wsol {
+                report.buys += 1;
+                report.buy_sizes.push(trade.amount_in);
+            } else if trade.output_token == wsol {
+                report.sells += 1;
+            }
+
+            if trade.output_token != wsol {
+                let round = rounds.entry(trade.output_token).or_insert_with(|| OpenRound {
+                    opened_at: trade.timestamp,
+                    raw_amount: 0,
+                });
+                round.raw_amount += trade.amount_out;
+            }
+            if trade.input_token != wsol {
+                if let Some(round) = rounds.get_mut(&trade.input_token) {
+                    round.raw_amount = round.raw_amount.saturating_sub(trade.amount_in);
+                    if round.raw_amount == 0 {
+                        report.hold_durations.push(trade.timestamp - round.opened_at);
+                        rounds.remove(&trade.input_token);
+                    }
+                }
+            }
+        }
+
+        report.open_positions = rounds.len();
+        report.buy_sizes.sort_unstable();
+        report.hold_durations.sort_unstable();
+        report.dex_counts = dexes.into_iter().collect();
+        report.dex_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        report
+    }
+}
+
+// 已排序数组的分位数
+fn percentile<T: Copy>(sorted: &[T], pct: usize) -> Option<T> {
+    if sorted.is_empty() {
+        return None;
+    }
+    Some(sorted[(sorted.len() - 1) * pct / 100])
+}
+
+fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3_600 => format!("{}m{}s", s / 60, s % 60),
+        s => format!("{}h{}m", s / 3_600, s % 3_600 / 60),
+    }
+}
+
+// 子命令analyze: 打印某个钱包在记录中的行为报告
+pub fn print_report(recorder: &TradeRecorder, wallet: &Pubkey, filter: &RecordFilter) -> Result<()> {
+    let filter = RecordFilter { wallet: Some(*wallet), ..filter.clone() };
+    let trades = recorder.read_records(&filter)?;
+    let report = WalletReport::from_trades(&trades);
+
+    println!("Wallet: {}", wallet);
+    println!("Trades: {} ({} buys, {} sells, {} open positions)",
+        report.trades, report.buys, report.sells, report.open_positions);
+    if report.trades == 0 {
+        return Ok(());
+    }
+
+    let sol = |lamports: u64| lamports as f64 / 1_000_000_000.0;
+    if let (Some(min), Some(p50), Some(p90), Some(max)) = (
+        percentile(&report.buy_sizes, 0),
+        percentile(&report.buy_sizes, 50),
+        percentile(&report.buy_sizes, 90),
+        percentile(&report.buy_sizes, 100),
+    ) {
+        println!("Buy size (SOL): min {:.4} / p50 {:.4} / p90 {:.4} / max {:.4}", sol(min), sol(p50), sol(p90), sol(max));
+    }
+    if let (Some(p10), Some(p50), Some(p90)) = (
+        percentile(&report.hold_durations, 10),
+        percentile(&report.hold_durations, 50),
+        percentile(&report.hold_durations, 90),
+    ) {
+        println!("Hold duration: p10 {} / p50 {} / p90 {} ({} closed)",
+            format_duration(p10), format_duration(p50), format_duration(p90), report.hold_durations.len());
+    }
+
+    println!("DEXes:");
+    for (dex, count) in &report.dex_counts {
+        println!("  {:<10} {:>5} ({:.0}%)", dex, count, *count as f64 * 100.0 / report.trades as f64);
+    }
+
+    println!("Activity by hour (UTC):");
+    let peak = report.hour_histogram.iter().copied().max().unwrap_or(0).max(1);
+    for (hour, count) in report.hour_histogram.iter().enumerate() {
+        if *count > 0 {
+            println!("  {:02}:00 {:>5} {}", hour, count, "#".repeat((count * 40).div_ceil(peak)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+
+    fn trade(input: Pubkey, output: Pubkey, amount_in: u64, amount_out: u64, timestamp: i64, dex: DexType) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex,
+            input_token: input,
+            output_token: output,
+            amount_in,
+            amount_out,
+            price: 0.0,
+            timestamp,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn round_closes_when_position_is_fully_sold() {
+        let wsol = spl_token::native_mint::id();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let report = WalletReport::from_trades(&[
+            trade(wsol, a, 1_000, 100, 3_600, DexType::PumpFun),
+            trade(wsol, b, 3_000, 50, 3_700, DexType::Raydium),
+            trade(a, wsol, 60, 900, 3_900, DexType::PumpFun),
+            trade(a, wsol, 40, 700, 4_200, DexType::PumpFun),
+        ]);
+
+        assert_eq!((report.trades, report.buys, report.sells), (4, 2, 2));
+        assert_eq!(report.buy_sizes, vec![1_000, 3_000]);
+        assert_eq!(report.hold_durations, vec![600]);
+        assert_eq!(report.open_positions, 1);
+        assert_eq!(report.dex_counts[0], ("Pump.fun".to_string(), 3));
+        assert_eq!(report.hour_histogram[1], 4);
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::types::{TradeAnnotation, TradeDetails};
+
+const ANNOTATIONS_FILE: &str = "annotations.json";
+
+// 按签名保存的交易标签和备注；记录文件只追加，标注单独存放在记录目录中
+pub struct AnnotationStore {
+    path: PathBuf,
+    annotations: Mutex<HashMap<String, TradeAnnotation>>,
+}
+
+impl AnnotationStore {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(ANNOTATIONS_FILE);
+        let annotations = read_file(&path)?;
+        Ok(AnnotationStore { path, annotations: Mutex::new(annotations) })
+    }
+
+    // 追加标签(去重)，备注有值时覆盖，返回合并后的标注
+    pub fn annotate(&self, signature: &str, tags: &[String], note: Option<String>) -> Result<TradeAnnotation> {
+        let mut annotations = self.annotations.lock().unwrap_or_else(|e| e.into_inner());
+        // 命令行和API可能同时修改，先合并磁盘上的最新内容
+        *annotations = read_file(&self.path)?;
+        let annotation = annotations.entry(signature.to_string()).or_default();
+        for tag in tags {
+            if !annotation.tags.contains(tag) {
+                annotation.tags.push(tag.clone());
+            }
+        }
+        if note.is_some() {
+            annotation.note = note;
+        }
+        let annotation = annotation.clone();
+
+        // 先写临时文件再重命名，避免进程中断留下半个文件
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&*annotations)?)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Unable to write {}", self.path.display()))?;
+        Ok(annotation)
+    }
+
+    pub fn apply(&self, trade: &mut TradeDetails) {
+        let annotations = self.annotations.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(annotation) = annotations.get(&trade.signature) {
+            trade.annotation = Some(annotation.clone());
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<HashMap<String, TradeAnnotation>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Invalid annotations file {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e).with_context(|| format!("Unable to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations_merge_and_persist() {
+        let dir = std::env::temp_dir().join(format!("annotations_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let store = AnnotationStore::load(&dir).unwrap();
+        store.annotate("sig", &["meme".to_string()], Some("first".to_string())).unwrap();
+        let merged = store.annotate("sig", &["meme".to_string(), "wallet A".to_string()], None).unwrap();
+        assert_eq!(merged.tags, vec!["meme".to_string(), "wallet A".to_string()]);
+        assert_eq!(merged.note.as_deref(), Some("first"));
+
+        let reloaded = AnnotationStore::load(&dir).unwrap();
+        assert_eq!(reloaded.annotations.lock().unwrap().get("sig"), Some(&merged));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
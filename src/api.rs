@@ -0,0 +1,366 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+use crate::annotations::AnnotationStore;
+use crate::bot_state::BotState;
+use crate::cluster::ClusterCandidate;
+use crate::explorer::Explorer;
+use crate::logging;
+use crate::rpc_budget::RpcMethodUsage;
+use crate::swap_prices::ObservedPrice;
+use crate::types::{TradeAnnotation, TradeDetails};
+use crate::watchlist::WatchedWalletSummary;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    pub bind: String,
+    pub token: String,
+}
+
+impl ApiConfig {
+    // 空token会让 "Authorization: Bearer " 通过校验
+    pub fn validate(&self) -> Result<()> {
+        if self.token.trim().is_empty() {
+            anyhow::bail!("api.token must not be empty");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    bot: Arc<BotState>,
+    token: Arc<String>,
+    // 以token为密钥对token本身的HMAC，请求携带的token按同样方式计算后做常量时间比较
+    token_mac: Arc<Vec<u8>>,
+    // 未配置recorder时为None
+    annotations: Option<Arc<AnnotationStore>>,
+    explorer: Arc<Explorer>,
+}
+
+#[derive(Deserialize)]
+struct AnnotationRequest {
+    #[serde(default)]
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LogFilter {
+    pub filter: String,
+}
+
+#[derive(Deserialize)]
+struct TradesQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct TradeResponse {
+    #[serde(flatten)]
+    trade: TradeDetails,
+    link: String,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    paused: bool,
+    connected: bool,
+    stream_slot: u64,
+    rpc_slot: u64,
+    stale_reconnects: u64,
+    rpc_calls: u64,
+    failed_attempts: u64,
+    pipeline_depth: u64,
+    pipeline_peak_depth: u64,
+    // 目标钱包自身成交的模拟盈亏
+    target_pnl: Option<WatchedWalletSummary>,
+}
+
+// HTTP控制接口，所有请求需携带 Authorization: Bearer <token>
+pub async fn serve(
+    config: ApiConfig,
+    bot: Arc<BotState>,
+    annotations: Option<Arc<AnnotationStore>>,
+    explorer: Explorer,
+) -> Result<()> {
+    config.validate()?;
+    let addr: SocketAddr = config.bind.parse()
+        .with_context(|| format!("Invalid API bind address {}", config.bind))?;
+
+    let state = ApiState::new(bot, config.token, annotations, explorer);
+
+    let app = Router::new()
+        .route("/trades", get(list_trades))
+        .route("/trades/:signature/annotations", post(annotate_trade))
+        .route("/status", get(status))
+        .route("/watchlist", get(watchlist))
+        .route("/rpc", get(rpc_usage))
+        .route("/skips", get(skip_counts))
+        .route("/compat", get(compat))
+        .route("/prices", get(swap_prices))
+        .route("/migrations", get(migrations))
+        .route("/cluster", get(cluster_candidates))
+        .route("/cluster/:wallet/watch", post(watch_cluster_candidate).delete(unwatch_cluster_candidate))
+        .route("/log-filter", get(log_filter).put(set_log_filter))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .with_state(state);
+
+    info!("REST API listening on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .context("REST API server failed")
+}
+
+// 命令行客户端：设置并返回运行中程序的日志过滤器，filter为None时只查询
+pub async fn request_log_filter(config: &ApiConfig, filter: Option<String>) -> Result<String> {
+    let mut addr: SocketAddr = config.bind.parse()
+        .with_context(|| format!("Invalid API bind address {}", config.bind))?;
+    if addr.ip().is_unspecified() {
+        addr.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+    }
+    let url = format!("http://{}/log-filter", addr);
+    let client = reqwest::Client::new();
+    if let Some(filter) = filter {
+        client.put(&url)
+            .bearer_auth(&config.token)
+            .json(&LogFilter { filter })
+            .send().await?
+            .error_for_status()
+            .context("The bot rejected the log filter")?;
+    }
+    let current: LogFilter = client.get(&url)
+        .bearer_auth(&config.token)
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    Ok(current.filter)
+}
+
+impl ApiState {
+    fn new(bot: Arc<BotState>, token: String, annotations: Option<Arc<AnnotationStore>>, explorer: Explorer) -> Self {
+        let token_mac = token_mac(&token, token.as_bytes()).finalize().into_bytes().to_vec();
+        ApiState {
+            bot,
+            token: Arc::new(token),
+            token_mac: Arc::new(token_mac),
+            annotations,
+            explorer: Arc::new(explorer),
+        }
+    }
+}
+
+fn token_mac(token: &str, value: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(token.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(value);
+    mac
+}
+
+fn authorize(headers: &HeaderMap, state: &ApiState) -> Result<(), StatusCode> {
+    let presented = headers.get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    // verify_slice是常量时间比较
+    token_mac(&state.token, presented.as_bytes())
+        .verify_slice(&state.token_mac)
+        .map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+async fn list_trades(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(query): Query<TradesQuery>,
+) -> Result<Json<Vec<TradeResponse>>, StatusCode> {
+    authorize(&headers, &state)?;
+    let trades = state.bot.recent_trades(query.limit.unwrap_or(50)).into_iter()
+        .map(|trade| TradeResponse { link: state.explorer.tx_url(&trade.signature), trade })
+        .collect();
+    Ok(Json(trades))
+}
+
+async fn annotate_trade(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(signature): Path<String>,
+    Json(request): Json<AnnotationRequest>,
+) -> Result<Json<TradeAnnotation>, StatusCode> {
+    authorize(&headers, &state)?;
+    let annotations = state.annotations.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let annotation = annotations.annotate(&signature, &request.tags, request.note)
+        .map_err(|e| {
+            warn!("Failed to annotate {}: {:?}", signature, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.bot.annotate_recent(&signature, &annotation);
+    Ok(Json(annotation))
+}
+
+async fn status(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<StatusResponse>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(StatusResponse {
+        paused: state.bot.is_paused(),
+        connected: state.bot.is_connected(),
+        stream_slot: state.bot.stream_slot(),
+        rpc_slot: state.bot.rpc_slot(),
+        stale_reconnects: state.bot.stale_reconnects(),
+        rpc_calls: state.bot.rpc_budget().total_calls(),
+        failed_attempts: state.bot.failed_attempts(),
+        pipeline_depth: state.bot.pipeline_depth(),
+        pipeline_peak_depth: state.bot.pipeline_peak_depth(),
+        target_pnl: state.bot.target_pnl_summary(),
+    }))
+}
+
+async fn rpc_usage(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<BTreeMap<String, RpcMethodUsage>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.rpc_budget().usage()))
+}
+
+async fn skip_counts(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<BTreeMap<String, u64>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.skip_counts()))
+}
+
+#[derive(Serialize)]
+struct CompatResponse {
+    provider_version: Option<String>,
+    issues: BTreeMap<String, u64>,
+}
+
+async fn compat(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<CompatResponse>, StatusCode> {
+    authorize(&headers, &state)?;
+    let compat = state.bot.stream_compat();
+    Ok(Json(CompatResponse {
+        provider_version: compat.provider_version(),
+        issues: compat.issues(),
+    }))
+}
+
+async fn swap_prices(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<HashMap<String, ObservedPrice>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.swap_prices().prices()))
+}
+
+// Pump.fun毕业代币 -> Raydium池子
+async fn migrations(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<HashMap<String, String>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.migrations()))
+}
+
+async fn cluster_candidates(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<Vec<ClusterCandidate>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.cluster().candidates()))
+}
+
+// 确认关联钱包候选，加入观察名单，在现有流上重新订阅
+async fn watch_cluster_candidate(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(wallet): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    authorize(&headers, &state)?;
+    let wallet = Pubkey::from_str(&wallet).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !state.bot.cluster().watch(&wallet) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    info!("Cluster wallet {} added to the watch list via API", wallet);
+    state.bot.request_resubscribe();
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unwatch_cluster_candidate(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(wallet): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    authorize(&headers, &state)?;
+    let wallet = Pubkey::from_str(&wallet).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !state.bot.cluster().unwatch(&wallet) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    info!("Cluster wallet {} removed from the watch list via API", wallet);
+    state.bot.request_resubscribe();
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn log_filter(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<LogFilter>, StatusCode> {
+    authorize(&headers, &state)?;
+    let filter = logging::current_filter().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(LogFilter { filter }))
+}
+
+// 运行中调整日志级别，格式同RUST_LOG
+async fn set_log_filter(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<LogFilter>,
+) -> Result<StatusCode, StatusCode> {
+    authorize(&headers, &state)?;
+    logging::set_filter(&request.filter).map_err(|e| {
+        warn!("Rejected log filter {:?}: {:?}", request.filter, e);
+        StatusCode::BAD_REQUEST
+    })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn watchlist(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<Vec<WatchedWalletSummary>>, StatusCode> {
+    authorize(&headers, &state)?;
+    Ok(Json(state.bot.watchlist().summaries()))
+}
+
+async fn pause(State(state): State<ApiState>, headers: HeaderMap) -> Result<StatusCode, StatusCode> {
+    authorize(&headers, &state)?;
+    state.bot.set_paused(true);
+    info!("Trade processing paused via API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn resume(State(state): State<ApiState>, headers: HeaderMap) -> Result<StatusCode, StatusCode> {
+    authorize(&headers, &state)?;
+    state.bot.set_paused(false);
+    info!("Trade processing resumed via API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::AUTHORIZATION;
+
+    fn headers(value: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = value {
+            headers.insert(AUTHORIZATION, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn authorizes_only_the_configured_bearer_token() {
+        let state = ApiState::new(Arc::new(BotState::new()), "secret".to_string(), None, Explorer::default());
+
+        assert!(authorize(&headers(Some("Bearer secret")), &state).is_ok());
+        assert_eq!(authorize(&headers(Some("Bearer other")), &state), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(authorize(&headers(Some("Bearer ")), &state), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(authorize(&headers(Some("secret")), &state), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(authorize(&headers(None), &state), Err(StatusCode::UNAUTHORIZED));
+
+        let empty = ApiConfig { bind: "127.0.0.1:8080".to_string(), token: " ".to_string() };
+        assert!(empty.validate().is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tracing::warn;
+use crate::types::TradeDetails;
+use crate::webhook::sign;
+
+// 链首条目的prev_mac
+const GENESIS_MAC: &str = "";
+
+// 分发决策审计日志：只追加，每条记录的HMAC覆盖上一条的HMAC，修改或删除任一条都会使之后的校验失败
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default = "default_file")]
+    pub file: String,
+    pub secret: String,
+}
+
+fn default_file() -> String {
+    "audit.log".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub timestamp: i64,
+    pub signature: String,
+    pub wallet: String,
+    pub dex: String,
+    pub input_token: String,
+    pub output_token: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub copied: bool,
+    pub skip_reason: Option<String>,
+    // 做出决策时配置的SHA-256
+    pub config_hash: String,
+    pub prev_mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AuditLine {
+    #[serde(flatten)]
+    record: AuditRecord,
+    mac: String,
+}
+
+struct AuditChain {
+    file: File,
+    next_seq: u64,
+    last_mac: String,
+}
+
+pub struct AuditLog {
+    secret: String,
+    config_hash: String,
+    chain: Mutex<AuditChain>,
+}
+
+impl AuditLog {
+    // 打开时校验已有记录并接在链尾继续写入，链被破坏时拒绝启动
+    pub fn open(config: &AuditConfig, config_hash: String) -> Result<Self> {
+        let (next_seq, last_mac) = match File::open(&config.file) {
+            Ok(file) => verify_chain(file, &config.secret)
+                .with_context(|| format!("Audit log {} failed verification", config.file))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, GENESIS_MAC.to_string()),
+            Err(e) => return Err(e).with_context(|| format!("Unable to read audit log {}", config.file)),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.file)
+            .with_context(|| format!("Unable to open audit log {}", config.file))?;
+        Ok(AuditLog {
+            secret: config.secret.clone(),
+            config_hash,
+            chain: Mutex::new(AuditChain { file, next_seq, last_mac }),
+        })
+    }
+
+    // 在阻塞线程上按顺序写入；队列不设上限，审计记录不能像交易记录那样在队列满时丢弃
+    pub fn spawn(self) -> AuditSink {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<TradeDetails>();
+        tokio::task::spawn_blocking(move || {
+            while let Some(trade) = receiver.blocking_recv() {
+                if let Err(e) = self.record(&trade) {
+                    warn!("Failed to write audit entry for {}: {:?}", trade.signature, e);
+                }
+            }
+        });
+        AuditSink { sender }
+    }
+
+    // 同步追加并刷新到文件
+    pub fn record(&self, trade: &TradeDetails) -> Result<()> {
+        let mut chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+        let record = AuditRecord {
+            seq: chain.next_seq,
+            timestamp: chrono::Utc::now().timestamp(),
+            signature: trade.signature.clone(),
+            wallet: trade.wallet.to_string(),
+            dex: trade.dex.to_string(),
+            input_token: trade.input_token.to_string(),
+            output_token: trade.output_token.to_string(),
+            amount_in: trade.amount_in,
+            amount_out: trade.amount_out,
+            copied: trade.skip_reason.is_none(),
+            skip_reason: trade.skip_reason.as_ref().map(|reason| reason.to_string()),
+            config_hash: self.config_hash.clone(),
+            prev_mac: chain.last_mac.clone(),
+        };
+        let mac = sign(&self.secret, &serde_json::to_vec(&record)?);
+        let mut line = serde_json::to_vec(&AuditLine { record, mac: mac.clone() })?;
+        line.push(b'\n');
+        chain.file.write_all(&line)?;
+        chain.file.flush()?;
+        chain.next_seq += 1;
+        chain.last_mac = mac;
+        Ok(())
+    }
+}
+
+// 审计日志的写入端，不阻塞调用方
+pub struct AuditSink {
+    sender: mpsc::UnboundedSender<TradeDetails>,
+}
+
+impl AuditSink {
+    pub fn record(&self, trade: &TradeDetails) {
+        if self.sender.send(trade.clone()).is_err() {
+            warn!("Audit writer stopped, entry for {} not written", trade.signature);
+        }
+    }
+}
+
+// 校验整个文件，返回记录条数
+pub fn verify(path: &Path, secret: &str) -> Result<u64> {
+    let file = File::open(path).with_context(|| format!("Unable to read audit log {}", path.display()))?;
+    Ok(verify_chain(file, secret)?.0)
+}
+
+fn verify_chain(file: File, secret: &str) -> Result<(u64, String)> {
+    let (mut next_seq, mut last_mac) = (0, GENESIS_MAC.to_string());
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let entry: AuditLine = serde_json::from_str(&line)
+            .with_context(|| format!("Malformed audit entry on line {}", index + 1))?;
+        if entry.record.seq != next_seq {
+            bail!("Audit entry on line {} has seq {}, expected {}", index + 1, entry.record.seq, next_seq);
+        }
+        if entry.record.prev_mac != last_mac {
+            bail!("Audit entry {} does not chain to the previous entry", entry.record.seq);
+        }
+        if sign(secret, &serde_json::to_vec(&entry.record)?) != entry.mac {
+            bail!("Audit entry {} has an invalid MAC", entry.record.seq);
+        }
+        next_seq += 1;
+        last_mac = entry.mac;
+    }
+    Ok((next_seq, last_mac))
+}
+
+pub fn print_verification(config: &AuditConfig) -> Result<()> {
+    let entries = verify(Path::new(&config.file), &config.secret)?;
+    println!("{}: {} entries, chain intact", config.file, entries);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, SkipReason, TradeCosts, TradeTimeline};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn chain_detects_modified_entries_and_resumes_after_reopen() {
+        let path = std::env::temp_dir().join(format!("audit_test_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = AuditConfig { file: path.to_string_lossy().to_string(), secret: "secret".to_string() };
+        let mut trade = TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: spl_token::native_mint::id(),
+            output_token: Pubkey::new_unique(),
+            amount_in: 1_000,
+            amount_out: 5_000,
+            price: 5.0,
+            timestamp: 0,
+            token_decimals: None,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        };
+
+        AuditLog::open(&config, "hash".to_string()).unwrap().record(&trade).unwrap();
+        trade.skip_reason = Some(SkipReason::Paused);
+        AuditLog::open(&config, "hash".to_string()).unwrap().record(&trade).unwrap();
+        assert_eq!(verify(&path, "secret").unwrap(), 2);
+        assert!(verify(&path, "other").is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replacen("\"amount_in\":1000", "\"amount_in\":9000", 1)).unwrap();
+        assert!(verify(&path, "secret").is_err());
+        assert!(AuditLog::open(&config, "hash".to_string()).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,252 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use tokio::sync::Notify;
+use crate::cluster::ClusterTracker;
+use crate::digest::Digest;
+use crate::rpc_budget::RpcBudget;
+use crate::stream_compat::StreamCompat;
+use crate::swap_prices::SwapPriceCache;
+use crate::types::{SkipReason, TradeAnnotation, TradeDetails};
+use crate::watchlist::{WatchedWalletSummary, Watchlist};
+
+// 保留的最近交易数量
+const MAX_RECENT_TRADES: usize = 500;
+
+/// 监控器与API等组件共享的运行时状态
+pub struct BotState {
+    recent_trades: RwLock<VecDeque<TradeDetails>>,
+    paused: AtomicBool,
+    connected: AtomicBool,
+    stream_slot: AtomicU64,
+    rpc_slot: AtomicU64,
+    stale_reconnects: AtomicU64,
+    failed_attempts: AtomicU64,
+    pipeline_depth: AtomicU64,
+    pipeline_peak_depth: AtomicU64,
+    watchlist: Watchlist,
+    target_pnl: Watchlist,
+    cluster: ClusterTracker,
+    // 监控的钱包在运行中变化，等待在现有流上重新订阅
+    subscription_changed: Notify,
+    digest: Digest,
+    rpc_budget: RpcBudget,
+    skip_counts: Mutex<BTreeMap<&'static str, u64>>,
+    stream_compat: StreamCompat,
+    swap_prices: SwapPriceCache,
+    // Pump.fun毕业代币 -> 迁移后的Raydium池子
+    migrations: RwLock<HashMap<Pubkey, Pubkey>>,
+}
+
+impl Default for BotState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BotState {
+    /// 空状态，未暂停、未连接
+    pub fn new() -> Self {
+        BotState {
+            recent_trades: RwLock::new(VecDeque::with_capacity(MAX_RECENT_TRADES)),
+            paused: AtomicBool::new(false),
+            connected: AtomicBool::new(false),
+            stream_slot: AtomicU64::new(0),
+            rpc_slot: AtomicU64::new(0),
+            stale_reconnects: AtomicU64::new(0),
+            failed_attempts: AtomicU64::new(0),
+            pipeline_depth: AtomicU64::new(0),
+            pipeline_peak_depth: AtomicU64::new(0),
+            watchlist: Watchlist::default(),
+            target_pnl: Watchlist::default(),
+            cluster: ClusterTracker::default(),
+            subscription_changed: Notify::new(),
+            digest: Digest::default(),
+            rpc_budget: RpcBudget::default(),
+            skip_counts: Mutex::new(BTreeMap::new()),
+            stream_compat: StreamCompat::default(),
+            swap_prices: SwapPriceCache::default(),
+            migrations: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn push_trade(&self, trade: TradeDetails) {
+        let mut trades = self.recent_trades.write().unwrap_or_else(|e| e.into_inner());
+        if trades.len() == MAX_RECENT_TRADES {
+            trades.pop_front();
+        }
+        trades.push_back(trade);
+    }
+
+    /// 给最近交易中的该笔交易附上标签和备注
+    pub fn annotate_recent(&self, signature: &str, annotation: &TradeAnnotation) {
+        let mut trades = self.recent_trades.write().unwrap_or_else(|e| e.into_inner());
+        for trade in trades.iter_mut().filter(|t| t.signature == signature) {
+            trade.annotation = Some(annotation.clone());
+        }
+    }
+
+    /// 最近的交易，新的在前
+    pub fn recent_trades(&self, limit: usize) -> Vec<TradeDetails> {
+        let trades = self.recent_trades.read().unwrap_or_else(|e| e.into_inner());
+        trades.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// 暂停时成交仍然记录，但不分发
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// 暂停或恢复分发
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// gRPC流是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    /// 监控器发起的RPC调用共用的限速预算和调用统计
+    pub fn rpc_budget(&self) -> &RpcBudget {
+        &self.rpc_budget
+    }
+
+    pub(crate) fn stream_compat(&self) -> &StreamCompat {
+        &self.stream_compat
+    }
+
+    /// 由监控到的成交得出的代币价格
+    pub(crate) fn swap_prices(&self) -> &SwapPriceCache {
+        &self.swap_prices
+    }
+
+    pub(crate) fn record_migration(&self, mint: Pubkey, pool: Pubkey) {
+        self.migrations.write().unwrap_or_else(|e| e.into_inner()).insert(mint, pool);
+    }
+
+    /// 代币毕业后的Raydium池子，未迁移或未观察到迁移时为None
+    pub fn migrated_pool(&self, mint: &Pubkey) -> Option<Pubkey> {
+        self.migrations.read().unwrap_or_else(|e| e.into_inner()).get(mint).copied()
+    }
+
+    /// 观察到的Pump.fun毕业迁移：代币 -> Raydium池子
+    pub fn migrations(&self) -> HashMap<String, String> {
+        let migrations = self.migrations.read().unwrap_or_else(|e| e.into_inner());
+        migrations.iter().map(|(mint, pool)| (mint.to_string(), pool.to_string())).collect()
+    }
+
+    /// 定时摘要的累计数据
+    pub fn digest(&self) -> &Digest {
+        &self.digest
+    }
+
+    /// 只观察不跟单的候选钱包
+    pub fn watchlist(&self) -> &Watchlist {
+        &self.watchlist
+    }
+
+    /// 按目标钱包自己的成交模拟的盈亏，不依赖是否跟单
+    pub fn target_pnl(&self) -> &Watchlist {
+        &self.target_pnl
+    }
+
+    /// 关联钱包候选
+    pub fn cluster(&self) -> &ClusterTracker {
+        &self.cluster
+    }
+
+    /// 监控的钱包变化后调用，监控器会在现有流上重新订阅；未连接时请求会保留到下一次订阅的流上
+    pub fn request_resubscribe(&self) {
+        self.subscription_changed.notify_one();
+    }
+
+    pub(crate) async fn subscription_changed(&self) {
+        self.subscription_changed.notified().await
+    }
+
+    /// 用最新的成交价重新估值持仓后取目标钱包的模拟盈亏
+    pub fn target_pnl_summary(&self) -> Option<WatchedWalletSummary> {
+        self.target_pnl.refresh_marks(&self.swap_prices);
+        self.target_pnl.summaries().into_iter().next()
+    }
+
+    /// gRPC流上最近看到的slot，0表示尚未收到
+    pub fn stream_slot(&self) -> u64 {
+        self.stream_slot.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_stream_slot(&self, slot: u64) {
+        self.stream_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// RPC getSlot的最近结果，0表示尚未获取
+    pub fn rpc_slot(&self) -> u64 {
+        self.rpc_slot.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_rpc_slot(&self, slot: u64) {
+        self.rpc_slot.store(slot, Ordering::Relaxed);
+    }
+
+    /// 因流停滞或滞后而主动重连的次数
+    pub fn stale_reconnects(&self) -> u64 {
+        self.stale_reconnects.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_stale_reconnect(&self) {
+        self.stale_reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 目标钱包失败的成交尝试次数
+    pub fn failed_attempts(&self) -> u64 {
+        self.failed_attempts.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_failed_attempt(&self) {
+        self.failed_attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 处理流水线中等待解析或处理的消息数
+    pub fn pipeline_depth(&self) -> u64 {
+        self.pipeline_depth.load(Ordering::Relaxed)
+    }
+
+    /// 启动以来流水线深度的峰值
+    pub fn pipeline_peak_depth(&self) -> u64 {
+        self.pipeline_peak_depth.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_pipeline_depth(&self, depth: usize) {
+        self.pipeline_depth.store(depth as u64, Ordering::Relaxed);
+        self.pipeline_peak_depth.fetch_max(depth as u64, Ordering::Relaxed);
+    }
+
+    /// 从快照恢复计数
+    pub fn restore_counters(&self, skip_counts: &BTreeMap<String, u64>, failed_attempts: u64) {
+        let mut counts = self.skip_counts.lock().unwrap_or_else(|e| e.into_inner());
+        for (code, count) in skip_counts {
+            // 计数表以&'static str为键，只恢复仍然存在的原因
+            if let Some(code) = SkipReason::CODES.iter().find(|c| **c == code) {
+                counts.insert(code, *count);
+            }
+        }
+        self.failed_attempts.store(failed_attempts, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_skip(&self, reason: &SkipReason) {
+        let mut counts = self.skip_counts.lock().unwrap_or_else(|e| e.into_inner());
+        *counts.entry(reason.code()).or_default() += 1;
+    }
+
+    /// 按原因统计的未分发交易数
+    pub fn skip_counts(&self) -> BTreeMap<String, u64> {
+        let counts = self.skip_counts.lock().unwrap_or_else(|e| e.into_inner());
+        counts.iter().map(|(code, count)| (code.to_string(), *count)).collect()
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::types::TransferEvent;
+
+// 目标钱包关联地址检测：目标向新地址转出大额SOL时标记为候选，确认(或auto_watch)后加入观察名单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    #[serde(default = "default_min_transfer_sol")]
+    pub min_transfer_sol: f64,
+    // 只标记转账前余额为0的地址
+    #[serde(default = "default_require_new_address")]
+    pub require_new_address: bool,
+    // 不等确认，直接加入观察名单
+    #[serde(default)]
+    pub auto_watch: bool,
+}
+
+fn default_min_transfer_sol() -> f64 {
+    1.0
+}
+
+fn default_require_new_address() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateStatus {
+    // 等待通过API确认
+    Pending,
+    // 已加入观察名单
+    Watched,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterCandidate {
+    pub wallet: Pubkey,
+    pub funded_by: Pubkey,
+    pub funding_signature: String,
+    pub funding_lamports: u64,
+    pub detected_at: i64,
+    pub status: CandidateStatus,
+    // 加入观察名单后观察到的成交数
+    pub trades: u64,
+}
+
+#[derive(Default)]
+pub struct ClusterTracker {
+    candidates: Mutex<HashMap<Pubkey, ClusterCandidate>>,
+}
+
+impl ClusterTracker {
+    // 符合条件的转出返回新候选，已标记过的地址返回None
+    pub fn flag(&self, config: &ClusterConfig, transfer: &TransferEvent) -> Option<ClusterCandidate> {
+        let to = transfer.to?;
+        let min_lamports = (config.min_transfer_sol * 1_000_000_000.0) as u64;
+        if transfer.incoming
+            || transfer.mint != spl_token::native_mint::id()
+            || transfer.amount < min_lamports
+            || (config.require_new_address && !transfer.new_counterparty)
+        {
+            return None;
+        }
+        let mut candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        if candidates.contains_key(&to) {
+            return None;
+        }
+        let candidate = ClusterCandidate {
+            wallet: to,
+            funded_by: transfer.wallet,
+            funding_signature: transfer.signature.clone(),
+            funding_lamports: transfer.amount,
+            detected_at: chrono::Utc::now().timestamp(),
+            status: if config.auto_watch { CandidateStatus::Watched } else { CandidateStatus::Pending },
+            trades: 0,
+        };
+        candidates.insert(to, candidate.clone());
+        Some(candidate)
+    }
+
+    // 确认候选，返回是否存在该候选
+    pub fn watch(&self, wallet: &Pubkey) -> bool {
+        self.set_status(wallet, CandidateStatus::Watched)
+    }
+
+    // 移出观察名单，恢复为待确认
+    pub fn unwatch(&self, wallet: &Pubkey) -> bool {
+        self.set_status(wallet, CandidateStatus::Pending)
+    }
+
+    fn set_status(&self, wallet: &Pubkey, status: CandidateStatus) -> bool {
+        let mut candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(candidate) = candidates.get_mut(wallet) else { return false };
+        candidate.status = status;
+        true
+    }
+
+    pub fn watched(&self) -> Vec<Pubkey> {
+        let candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        candidates.values()
+            .filter(|c| c.status == CandidateStatus::Watched)
+            .map(|c| c.wallet)
+            .collect()
+    }
+
+    // 记录观察到的成交，返回候选的第一笔成交
+    pub fn record_trade(&self, wallet: &Pubkey) -> Option<ClusterCandidate> {
+        let mut candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        let candidate = candidates.get_mut(wallet)?;
+        candidate.trades += 1;
+        (candidate.trades == 1).then(|| candidate.clone())
+    }
+
+    pub fn candidates(&self) -> Vec<ClusterCandidate> {
+        let candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<ClusterCandidate> = candidates.values().cloned().collect();
+        list.sort_by_key(|c| c.detected_at);
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_large_sol_transfers_to_new_addresses_once() {
+        let config = ClusterConfig { min_transfer_sol: 1.0, require_new_address: true, auto_watch: false };
+        let (target, fresh) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut transfer = TransferEvent {
+            signature: "sig".to_string(),
+            wallet: target,
+            mint: spl_token::native_mint::id(),
+            from: Some(target),
+            to: Some(fresh),
+            amount: 2_000_000_000,
+            incoming: false,
+            new_counterparty: true,
+        };
+        let tracker = ClusterTracker::default();
+
+        assert_eq!(tracker.flag(&config, &transfer).unwrap().status, CandidateStatus::Pending);
+        assert!(tracker.flag(&config, &transfer).is_none());
+        assert!(tracker.watched().is_empty());
+        assert!(tracker.watch(&fresh));
+        assert_eq!(tracker.watched(), vec![fresh]);
+        assert!(tracker.record_trade(&fresh).is_some());
+        assert!(tracker.record_trade(&fresh).is_none());
+        assert!(tracker.unwatch(&fresh));
+        assert!(tracker.watched().is_empty());
+
+        transfer.to = Some(Pubkey::new_unique());
+        transfer.new_counterparty = false;
+        assert!(tracker.flag(&config, &transfer).is_none());
+    }
+}
@@ -1,26 +1,156 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::BTreeMap;
 use std::fs;
+use sha2::{Digest, Sha256};
+use wallet_copier::api::ApiConfig;
+use wallet_copier::audit::AuditConfig;
+use wallet_copier::cluster::ClusterConfig;
+use wallet_copier::digest::DigestConfig;
+use wallet_copier::explorer::Explorer;
+use wallet_copier::gap_recovery::GapRecoveryConfig;
+use wallet_copier::grpc_monitor::{ChannelConfig, DexSwitches, NewPoolConfig, PipelineConfig, TransactionFilterConfig};
+use wallet_copier::parse_failures::ParseFailureConfig;
+use wallet_copier::profiles::{self, TradingSettings, WalletProfile};
+use wallet_copier::recorder::RecorderConfig;
+use wallet_copier::rpc_budget::RpcBudgetConfig;
+use wallet_copier::snapshot::SnapshotConfig;
+use wallet_copier::strategy::MomentumConfig;
+use wallet_copier::stream_health::StreamHealthConfig;
+use wallet_copier::webhook::WebhookConfig;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub rpc_url: String,
+    pub grpc_endpoint: String,
+    #[serde(default)]
+    pub grpc_auth_token: Option<String>,
     pub target_wallets: Vec<String>,
+    // 只观察记录、不跟单的候选钱包，确认后移入target_wallets
+    #[serde(default)]
+    pub watch_wallets: Vec<String>,
     pub copy_wallet_private_key: String,
     pub trading_settings: TradingSettings,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TradingSettings {
-    pub max_position_size: f64,
-    pub slippage_tolerance: f64,
-    pub gas_price_multiplier: f64,
+    // 目标钱包 -> 使用的profile(sniper/conservative/mirror)及单项覆盖，未列出的钱包使用trading_settings
+    // 目前没有执行器，合并后的参数只用于启动日志和profiles show，不影响任何决策
+    // 使用BTreeMap保证序列化顺序固定，hash()才能在重启间保持一致
+    #[serde(default)]
+    pub target_profiles: BTreeMap<String, WalletProfile>,
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    #[serde(default)]
+    pub api: Option<ApiConfig>,
+    #[serde(default)]
+    pub stream_health: StreamHealthConfig,
+    #[serde(default)]
+    pub gap_recovery: Option<GapRecoveryConfig>,
+    // 已处理交易签名的持久化文件，未配置时只在内存中去重
+    #[serde(default)]
+    pub ledger_file: Option<String>,
+    #[serde(default)]
+    pub recorder: Option<RecorderConfig>,
+    #[serde(default)]
+    pub transaction_filter: TransactionFilterConfig,
+    // 计价代币(WSOL/USDC/USDT等)，为空时使用默认集合
+    #[serde(default)]
+    pub quote_mints: Vec<String>,
+    #[serde(default)]
+    pub dex_switches: DexSwitches,
+    // 定时输出交易摘要，未配置时不输出
+    #[serde(default)]
+    pub digest: Option<DigestConfig>,
+    #[serde(default)]
+    pub rpc_budget: RpcBudgetConfig,
+    // 新池子检测模式，未配置时不订阅
+    #[serde(default)]
+    pub new_pools: Option<NewPoolConfig>,
+    // 决定是否分发成交的策略名称，默认mirror
+    #[serde(default)]
+    pub strategy: Option<String>,
+    // 买入前的动量过滤，未配置时不检查
+    #[serde(default)]
+    pub momentum: Option<MomentumConfig>,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    // 运行时状态快照，未配置时重启后从空状态开始
+    #[serde(default)]
+    pub snapshot: Option<SnapshotConfig>,
+    // 交易和账户链接使用的区块浏览器: solscan/solana_fm/xray/{"custom": {"base": ...}}
+    #[serde(default)]
+    pub explorer: Explorer,
+    // 日志过滤器，格式同RUST_LOG(如 "info,wallet_copier::parser=debug")；设置了RUST_LOG时以环境变量为准
+    #[serde(default)]
+    pub log_filter: Option<String>,
+    // 保存已知DEX交易的解析失败，未配置时只输出警告
+    #[serde(default)]
+    pub parse_failures: Option<ParseFailureConfig>,
+    // 分发决策审计日志(HMAC链)，未配置时不写入
+    #[serde(default)]
+    pub audit: Option<AuditConfig>,
+    // 目标钱包关联地址检测，未配置时不检测
+    #[serde(default)]
+    pub cluster: Option<ClusterConfig>,
+    // gRPC压缩、keepalive和消息大小上限
+    #[serde(default)]
+    pub grpc_channel: ChannelConfig,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
         let config_str = fs::read_to_string("config.json")?;
         let config: Config = serde_json::from_str(&config_str)?;
+        if let Some(api) = &config.api {
+            api.validate()?;
+        }
         Ok(config)
     }
-}
\ No newline at end of file
+
+    // 从私钥得到跟单钱包地址
+    pub fn copy_wallet_pubkey(&self) -> Result<Pubkey> {
+        let bytes = bs58::decode(&self.copy_wallet_private_key)
+            .into_vec()
+            .context("Invalid copy wallet private key encoding")?;
+        let keypair = Keypair::from_bytes(&bytes)
+            .map_err(|e| anyhow::anyhow!("Invalid copy wallet private key: {}", e))?;
+        Ok(keypair.pubkey())
+    }
+
+    // 目标钱包合并profile和覆盖后的交易参数，返回profile名称
+    pub fn settings_for(&self, wallet: &str) -> Result<(String, TradingSettings)> {
+        profiles::resolve(&self.trading_settings, self.target_profiles.get(wallet))
+            .with_context(|| format!("Invalid profile for target wallet {}", wallet))
+    }
+
+    // 审计日志中记录的配置指纹
+    pub fn hash(&self) -> Result<String> {
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(self)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_is_stable_across_loads() {
+        let json = r#"{
+            "rpc_url": "http://localhost:8899",
+            "grpc_endpoint": "http://localhost:10000",
+            "target_wallets": ["a"],
+            "copy_wallet_private_key": "",
+            "trading_settings": {"max_position_size": 0.1, "slippage_tolerance": 0.05, "gas_price_multiplier": 1.0},
+            "target_profiles": {
+                "c": {"profile": "sniper"},
+                "a": {"profile": "conservative", "max_position_size": 0.01},
+                "b": {"profile": "mirror"},
+                "d": {"profile": "sniper", "stop_loss_pct": 5.0}
+            }
+        }"#;
+        let first: Config = serde_json::from_str(json).unwrap();
+        let second: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(first.hash().unwrap(), first.hash().unwrap());
+        assert_eq!(first.hash().unwrap(), second.hash().unwrap());
+    }
+}
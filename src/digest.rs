@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::info;
+use crate::bot_state::BotState;
+use crate::types::TradeDetails;
+use crate::watchlist::WatchedWalletSummary;
+use crate::webhook::WebhookSink;
+
+// 摘要中列出的代币/钱包数量
+const TOP_N: usize = 3;
+
+// 定时摘要配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestConfig {
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+}
+
+fn default_interval_minutes() -> u64 {
+    60
+}
+
+#[derive(Default)]
+struct DigestWindow {
+    started_at: i64,
+    detected: u64,
+    dispatched: u64,
+    fees_lamports: u64,
+    tips_lamports: u64,
+    sol_volume_lamports: u64,
+    // 每个代币的成交次数
+    mints: HashMap<Pubkey, u64>,
+}
+
+// 一个周期内的交易统计
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestSummary {
+    pub period_start: i64,
+    pub period_end: i64,
+    pub trades_detected: u64,
+    pub trades_dispatched: u64,
+    pub dispatch_rate: f64,
+    pub fees_sol: f64,
+    pub tips_sol: f64,
+    pub sol_volume: f64,
+    pub top_mints: Vec<(String, u64)>,
+    // 观察名单中模拟盈亏最高和最低的钱包
+    pub top_watched: Vec<WatchedWalletSummary>,
+    pub bottom_watched: Vec<WatchedWalletSummary>,
+    // 目标钱包自身成交的模拟盈亏(累计值，不随周期清零)
+    pub target: Option<WatchedWalletSummary>,
+}
+
+// 按周期累计检测到的交易，到期后取出摘要并清零
+pub struct Digest {
+    window: Mutex<DigestWindow>,
+}
+
+impl Default for Digest {
+    fn default() -> Self {
+        Digest {
+            window: Mutex::new(DigestWindow {
+                started_at: chrono::Utc::now().timestamp(),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl Digest {
+    pub fn record(&self, trade: &TradeDetails, dispatched: bool) {
+        let wsol = spl_token::native_mint::id();
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        window.detected += 1;
+        if dispatched {
+            window.dispatched += 1;
+        }
+        window.fees_lamports += trade.costs.base_fee + trade.costs.priority_fee;
+        window.tips_lamports += trade.costs.tip;
+        if trade.input_token == wsol {
+            window.sol_volume_lamports += trade.amount_in;
+        } else if trade.output_token == wsol {
+            window.sol_volume_lamports += trade.amount_out;
+        }
+        let token = if trade.input_token == wsol { trade.output_token } else { trade.input_token };
+        *window.mints.entry(token).or_default() += 1;
+    }
+
+    pub fn take(&self, watched: Vec<WatchedWalletSummary>, target: Option<WatchedWalletSummary>) -> DigestSummary {
+        let now = chrono::Utc::now().timestamp();
+        let window = {
+            let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *window, DigestWindow { started_at: now, ..Default::default() })
+        };
+
+        let mut top_mints: Vec<(String, u64)> = window.mints.iter()
+            .map(|(mint, count)| (mint.to_string(), *count))
+            .collect();
+        top_mints.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_mints.truncate(TOP_N);
+
+        // summaries已按盈亏从高到低排序
+        let top_watched: Vec<_> = watched.iter().take(TOP_N).cloned().collect();
+        let bottom_watched: Vec<_> = watched.iter().rev().take(TOP_N)
+            .filter(|w| !top_watched.iter().any(|t| t.wallet == w.wallet))
+            .cloned()
+            .collect();
+
+        let sol = |lamports: u64| lamports as f64 / 1_000_000_000.0;
+        DigestSummary {
+            period_start: window.started_at,
+            period_end: now,
+            trades_detected: window.detected,
+            trades_dispatched: window.dispatched,
+            dispatch_rate: if window.detected > 0 { window.dispatched as f64 / window.detected as f64 } else { 0.0 },
+            fees_sol: sol(window.fees_lamports),
+            tips_sol: sol(window.tips_lamports),
+            sol_volume: sol(window.sol_volume_lamports),
+            top_mints,
+            top_watched,
+            bottom_watched,
+            target,
+        }
+    }
+}
+
+fn log_summary(summary: &DigestSummary) {
+    let minutes = (summary.period_end - summary.period_start) / 60;
+    info!("╔═══════════════ Trade digest ({} min) ═══════════════", minutes);
+    info!("║ Trades: {} detected, {} dispatched ({:.0}%)",
+        summary.trades_detected, summary.trades_dispatched, summary.dispatch_rate * 100.0);
+    info!("║ Volume: {:.4} SOL | fees {:.6} SOL | tips {:.6} SOL", summary.sol_volume, summary.fees_sol, summary.tips_sol);
+    for (mint, count) in &summary.top_mints {
+        info!("║ Top token: {} ({} trades)", mint, count);
+    }
+    if let Some(target) = &summary.target {
+        info!("║ Target {}: {:+.4} SOL over {} trades ({} open positions)",
+            target.wallet, target.hypothetical_pnl_sol, target.trades, target.open_positions);
+    }
+    for wallet in summary.top_watched.iter().chain(&summary.bottom_watched) {
+        info!("║ Watched {}: {:+.4} SOL over {} trades", wallet.wallet, wallet.hypothetical_pnl_sol, wallet.trades);
+    }
+    info!("╚════════════════════════════════════════════════════");
+}
+
+// 按配置周期输出摘要，配置了webhook时同时推送
+pub fn spawn(config: DigestConfig, state: Arc<BotState>, webhook: Option<Arc<WebhookSink>>) {
+    let period = Duration::from_secs(config.interval_minutes.max(1) * 60);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        // 第一次tick立即触发，跳过
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let summary = state.digest().take(state.watchlist().summaries(), state.target_pnl_summary());
+            log_summary(&summary);
+            if let Some(webhook) = &webhook {
+                webhook.send_digest(&summary);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+
+    fn trade(input: Pubkey, output: Pubkey, amount_in: u64, amount_out: u64) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: input,
+            output_token: output,
+            amount_in,
+            amount_out,
+            price: 0.0,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts { base_fee: 5_000, priority_fee: 10_000, tip: 1_000 },
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn take_summarises_and_resets_the_window() {
+        let digest = Digest::default();
+        let wsol = spl_token::native_mint::id();
+        let mint = Pubkey::new_unique();
+        digest.record(&trade(wsol, mint, 2_000_000_000, 100), true);
+        digest.record(&trade(mint, wsol, 100, 3_000_000_000), false);
+
+        let summary = digest.take(Vec::new(), None);
+        assert_eq!((summary.trades_detected, summary.trades_dispatched), (2, 1));
+        assert_eq!(summary.dispatch_rate, 0.5);
+        assert!((summary.sol_volume - 5.0).abs() < 1e-9);
+        assert!((summary.fees_sol - 0.00003).abs() < 1e-12);
+        assert_eq!(summary.top_mints, vec![(mint.to_string(), 2)]);
+
+        assert_eq!(digest.take(Vec::new(), None).trades_detected, 0);
+    }
+}
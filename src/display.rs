@@ -0,0 +1,89 @@
+use solana_sdk::pubkey::Pubkey;
+use crate::types::{QuoteMints, TradeDetails};
+
+// 前景色: 青、品红、黄、绿、蓝、红
+const WALLET_COLORS: [u8; 6] = [36, 35, 33, 32, 34, 31];
+
+// 交易输出的详细程度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    // 只输出成交，每笔一行
+    Quiet,
+    // 成交每笔一行，非成交交易合并为一行摘要
+    #[default]
+    Normal,
+    // 每笔交易输出完整的解析、余额变化和日志
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_args(args: &[String]) -> Self {
+        if args.iter().any(|arg| arg == "--verbose" || arg == "-v") {
+            Verbosity::Verbose
+        } else if args.iter().any(|arg| arg == "--quiet" || arg == "-q") {
+            Verbosity::Quiet
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+// 控制台上的交易输出：按钱包着色的前缀和单行摘要
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleOutput {
+    pub verbosity: Verbosity,
+    // 日志写入文件(TUI模式)时关闭颜色
+    pub colors: bool,
+}
+
+impl ConsoleOutput {
+    pub fn is_verbose(&self) -> bool {
+        self.verbosity == Verbosity::Verbose
+    }
+
+    // 同一钱包总是得到同一种颜色
+    pub fn wallet_prefix(&self, wallet: &Pubkey) -> String {
+        let address = wallet.to_string();
+        let label = format!("[{}..{}]", &address[..4], &address[address.len() - 4..]);
+        if !self.colors {
+            return label;
+        }
+        let color = WALLET_COLORS[wallet.as_ref()[0] as usize % WALLET_COLORS.len()];
+        format!("\x1b[{}m{}\x1b[0m", color, label)
+    }
+
+    pub fn trade_line(&self, trade: &TradeDetails, quotes: &QuoteMints) -> String {
+        let value = trade.value_usd.map(|usd| format!(" (${:.2})", usd)).unwrap_or_default();
+        let sold = trade.sell_fraction.map(|fraction| format!(" [{:.0}% of position]", fraction * 100.0)).unwrap_or_default();
+        let skipped = trade.skip_reason.as_ref().map(|reason| format!(" [not dispatched: {}]", reason)).unwrap_or_default();
+        format!("{} {} {} {} {} -> {} {}{}{}{} {}",
+            self.wallet_prefix(&trade.wallet), trade.dex, trade.direction(quotes),
+            trade.amount_in, trade.input_token, trade.amount_out, trade.output_token,
+            value, sold, skipped, short_signature(&trade.signature))
+    }
+}
+
+pub fn short_signature(signature: &str) -> String {
+    if signature.len() <= 16 {
+        return signature.to_string();
+    }
+    format!("{}...{}", &signature[..8], &signature[signature.len() - 8..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallet_prefix_is_stable_and_optionally_colored() {
+        let wallet = Pubkey::new_unique();
+        let plain = ConsoleOutput { verbosity: Verbosity::Normal, colors: false };
+        let colored = ConsoleOutput { colors: true, ..plain };
+        let address = wallet.to_string();
+
+        assert_eq!(plain.wallet_prefix(&wallet), format!("[{}..{}]", &address[..4], &address[address.len() - 4..]));
+        assert_eq!(colored.wallet_prefix(&wallet), colored.wallet_prefix(&wallet));
+        assert!(colored.wallet_prefix(&wallet).starts_with("\x1b["));
+        assert_eq!(Verbosity::from_args(&["--quiet".to_string()]), Verbosity::Quiet);
+    }
+}
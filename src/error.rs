@@ -0,0 +1,29 @@
+use thiserror::Error;
+use yellowstone_grpc_client::{GeyserGrpcBuilderError, GeyserGrpcClientError};
+use crate::stream_health::StreamProblem;
+
+// 交易解析失败：调用方通常跳过该笔交易继续处理
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("invalid account key in message")]
+    InvalidAccountKey,
+    #[error("{dex} curve account does not match mint {mint}")]
+    CurveMismatch { dex: &'static str, mint: String },
+    #[error("unable to determine {dex} {side} fill for {signature}")]
+    MissingFill { dex: &'static str, side: &'static str, signature: String },
+}
+
+// gRPC监控失败：决定是立即重连还是等待后重试
+#[derive(Debug, Error)]
+pub enum MonitorError {
+    #[error("unable to connect to gRPC service: {0}")]
+    Connect(#[from] GeyserGrpcBuilderError),
+    #[error("subscription failed: {0}")]
+    Subscribe(#[from] GeyserGrpcClientError),
+    #[error("failed to send subscription request")]
+    SubscribeRequest,
+    #[error("stream error: {0}")]
+    Stream(String),
+    #[error("stale stream: {0}")]
+    Stale(StreamProblem),
+}
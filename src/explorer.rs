@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+// 日志、通知和API中交易/账户链接使用的区块浏览器
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Explorer {
+    #[default]
+    Solscan,
+    SolanaFm,
+    Xray,
+    // 自定义地址，链接格式为 {base}/tx/{签名} 和 {base}/account/{地址}
+    Custom { base: String },
+}
+
+impl Explorer {
+    fn paths(&self) -> (&str, &str, &str) {
+        match self {
+            Explorer::Solscan => ("https://solscan.io", "tx", "account"),
+            Explorer::SolanaFm => ("https://solana.fm", "tx", "address"),
+            Explorer::Xray => ("https://xray.helius.xyz", "tx", "account"),
+            Explorer::Custom { base } => (base.trim_end_matches('/'), "tx", "account"),
+        }
+    }
+
+    pub fn tx_url(&self, signature: &str) -> String {
+        let (base, tx, _) = self.paths();
+        format!("{}/{}/{}", base, tx, signature)
+    }
+
+    pub fn account_url(&self, account: &Pubkey) -> String {
+        let (base, _, account_path) = self.paths();
+        format!("{}/{}/{}", base, account_path, account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_links_for_configured_explorer() {
+        let account = Pubkey::new_unique();
+        assert_eq!(Explorer::default().tx_url("sig"), "https://solscan.io/tx/sig");
+        assert_eq!(Explorer::SolanaFm.account_url(&account), format!("https://solana.fm/address/{}", account));
+
+        let custom: Explorer = serde_json::from_str(r#"{"custom":{"base":"https://explorer.example/"}}"#).unwrap();
+        assert_eq!(custom.tx_url("sig"), "https://explorer.example/tx/sig");
+        assert_eq!(serde_json::from_str::<Explorer>(r#""xray""#).unwrap(), Explorer::Xray);
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::bs58;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::{MessageHeader, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::{UiInstruction, UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance};
+use std::str::FromStr;
+use tracing::warn;
+use crate::rpc_budget::RpcBudget;
+use yellowstone_grpc_proto::convert_to;
+use yellowstone_grpc_proto::prelude as proto;
+
+// getSignaturesForAddress单页上限
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+// 断线补偿配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GapRecoveryConfig {
+    // 保存最后处理slot的文件
+    #[serde(default = "default_checkpoint_file")]
+    pub checkpoint_file: String,
+    // 单次补偿最多拉取的签名数
+    #[serde(default = "default_max_signatures")]
+    pub max_signatures: usize,
+    // 为true时补偿到的交易也会分发给下游，否则只记录
+    #[serde(default)]
+    pub copy_missed: bool,
+}
+
+fn default_checkpoint_file() -> String {
+    "last_slot.json".to_string()
+}
+
+fn default_max_signatures() -> usize {
+    500
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    slot: u64,
+}
+
+pub fn load_checkpoint(path: &str) -> Option<u64> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Checkpoint>(&content).ok().map(|c| c.slot)
+}
+
+// 先写临时文件再重命名，避免进程中断留下半个文件
+pub async fn save_checkpoint(path: &str, slot: u64) -> Result<()> {
+    let tmp = format!("{}.tmp", path);
+    tokio::fs::write(&tmp, serde_json::to_vec(&Checkpoint { slot })?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+// 通过RPC补回的交易，已转换成gRPC的结构以便复用解析器
+pub struct RecoveredTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: proto::Transaction,
+    pub meta: Option<proto::TransactionStatusMeta>,
+}
+
+// 拉取钱包在since_slot之后的成功交易，按时间从旧到新返回
+pub async fn fetch_missed_transactions(
+    rpc: &RpcClient,
+    budget: &RpcBudget,
+    wallet: &Pubkey,
+    since_slot: u64,
+    max_signatures: usize,
+) -> Result<Vec<RecoveredTransaction>> {
+    fetch_transactions(rpc, budget, wallet, max_signatures, |slot, _| slot <= since_slot).await
+}
+
+// 拉取钱包在since_time(unix秒)之后的成功交易，用于导入历史
+pub async fn fetch_transactions_since_time(
+    rpc: &RpcClient,
+    budget: &RpcBudget,
+    wallet: &Pubkey,
+    since_time: i64,
+    max_signatures: usize,
+) -> Result<Vec<RecoveredTransaction>> {
+    fetch_transactions(rpc, budget, wallet, max_signatures, |_, block_time| {
+        block_time.is_some_and(|t| t < since_time)
+    }).await
+}
+
+// 从新到旧翻页，reached_end(slot, block_time)为true时停止
+async fn fetch_transactions(
+    rpc: &RpcClient,
+    budget: &RpcBudget,
+    wallet: &Pubkey,
+    max_signatures: usize,
+    reached_end: impl Fn(u64, Option<i64>) -> bool,
+) -> Result<Vec<RecoveredTransaction>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+
+    'pages: loop {
+        budget.acquire("getSignaturesForAddress").await;
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                wallet,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURES_PAGE_LIMIT),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await
+            .context("getSignaturesForAddress failed")?;
+        let page_len = page.len();
+
+        for status in page {
+            if reached_end(status.slot, status.block_time) || signatures.len() >= max_signatures {
+                break 'pages;
+            }
+            before = Some(Signature::from_str(&status.signature)?);
+            if status.err.is_none() {
+                signatures.push((status.signature, status.slot, status.block_time));
+            }
+        }
+        if page_len < SIGNATURES_PAGE_LIMIT {
+            break;
+        }
+    }
+
+    let mut recovered = Vec::with_capacity(signatures.len());
+    for (signature, slot, block_time) in signatures.into_iter().rev() {
+        budget.acquire("getTransaction").await;
+        let tx = match rpc
+            .get_transaction_with_config(
+                &Signature::from_str(&signature)?,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+        {
+            Ok(tx) => tx,
+            Err(e) => {
+                warn!("getTransaction failed for {}: {}", signature, e);
+                continue;
+            }
+        };
+
+        let transaction = match tx.transaction.transaction.decode() {
+            Some(transaction) => proto_transaction(&transaction),
+            None => {
+                warn!("Unable to decode transaction {}", signature);
+                continue;
+            }
+        };
+        recovered.push(RecoveredTransaction {
+            signature,
+            slot,
+            block_time: tx.block_time.or(block_time),
+            transaction,
+            meta: tx.transaction.meta.as_ref().map(proto_meta),
+        });
+    }
+
+    Ok(recovered)
+}
+
+fn proto_header(header: &MessageHeader) -> proto::MessageHeader {
+    proto::MessageHeader {
+        num_required_signatures: header.num_required_signatures as u32,
+        num_readonly_signed_accounts: header.num_readonly_signed_accounts as u32,
+        num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts as u32,
+    }
+}
+
+pub fn proto_transaction(tx: &VersionedTransaction) -> proto::Transaction {
+    let message = match &tx.message {
+        VersionedMessage::Legacy(message) => proto::Message {
+            header: Some(proto_header(&message.header)),
+            account_keys: convert_to::create_pubkeys(&message.account_keys),
+            recent_blockhash: message.recent_blockhash.to_bytes().into(),
+            instructions: convert_to::create_instructions(&message.instructions),
+            versioned: false,
+            address_table_lookups: vec![],
+        },
+        VersionedMessage::V0(message) => proto::Message {
+            header: Some(proto_header(&message.header)),
+            account_keys: convert_to::create_pubkeys(&message.account_keys),
+            recent_blockhash: message.recent_blockhash.to_bytes().into(),
+            instructions: convert_to::create_instructions(&message.instructions),
+            versioned: true,
+            address_table_lookups: convert_to::create_lookups(&message.address_table_lookups),
+        },
+    };
+
+    proto::Transaction {
+        signatures: tx.signatures.iter().map(|s| s.as_ref().to_vec()).collect(),
+        message: Some(message),
+    }
+}
+
+fn proto_token_balance(balance: &UiTransactionTokenBalance) -> proto::TokenBalance {
+    proto::TokenBalance {
+        account_index: balance.account_index as u32,
+        mint: balance.mint.clone(),
+        ui_token_amount: Some(proto::UiTokenAmount {
+            ui_amount: balance.ui_token_amount.ui_amount.unwrap_or_default(),
+            decimals: balance.ui_token_amount.decimals as u32,
+            amount: balance.ui_token_amount.amount.clone(),
+            ui_amount_string: balance.ui_token_amount.ui_amount_string.clone(),
+        }),
+        owner: Option::from(balance.owner.clone()).unwrap_or_default(),
+        program_id: Option::from(balance.program_id.clone()).unwrap_or_default(),
+    }
+}
+
+fn decode_pubkeys(keys: &[String]) -> Vec<Vec<u8>> {
+    keys.iter()
+        .filter_map(|k| Pubkey::from_str(k).ok())
+        .map(|k| k.to_bytes().to_vec())
+        .collect()
+}
+
+// 只转换解析器和余额分析用到的字段
+pub fn proto_meta(meta: &UiTransactionStatusMeta) -> proto::TransactionStatusMeta {
+    let inner_instructions: Option<&Vec<_>> = meta.inner_instructions.as_ref().into();
+    let log_messages: Option<&Vec<String>> = meta.log_messages.as_ref().into();
+    let pre_token_balances: Option<&Vec<_>> = meta.pre_token_balances.as_ref().into();
+    let post_token_balances: Option<&Vec<_>> = meta.post_token_balances.as_ref().into();
+    let loaded_addresses: Option<&_> = meta.loaded_addresses.as_ref().into();
+
+    proto::TransactionStatusMeta {
+        err: meta.err.as_ref().map(|err| proto::TransactionError {
+            err: bincode::serialize(err).unwrap_or_default(),
+        }),
+        fee: meta.fee,
+        pre_balances: meta.pre_balances.clone(),
+        post_balances: meta.post_balances.clone(),
+        inner_instructions: inner_instructions.into_iter().flatten()
+            .map(|inner| proto::InnerInstructions {
+                index: inner.index as u32,
+                instructions: inner.instructions.iter()
+                    .filter_map(|ix| match ix {
+                        UiInstruction::Compiled(ix) => Some(proto::InnerInstruction {
+                            program_id_index: ix.program_id_index as u32,
+                            accounts: ix.accounts.clone(),
+                            data: bs58::decode(&ix.data).into_vec().ok()?,
+                            stack_height: ix.stack_height,
+                        }),
+                        UiInstruction::Parsed(_) => None,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        inner_instructions_none: inner_instructions.is_none(),
+        log_messages: log_messages.cloned().unwrap_or_default(),
+        log_messages_none: log_messages.is_none(),
+        pre_token_balances: pre_token_balances.into_iter().flatten().map(proto_token_balance).collect(),
+        post_token_balances: post_token_balances.into_iter().flatten().map(proto_token_balance).collect(),
+        loaded_writable_addresses: loaded_addresses.map(|a| decode_pubkeys(&a.writable)).unwrap_or_default(),
+        loaded_readonly_addresses: loaded_addresses.map(|a| decode_pubkeys(&a.readonly)).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::{UiCompiledInstruction, UiInnerInstructions, UiLoadedAddresses};
+    use solana_transaction_status::option_serializer::OptionSerializer;
+
+    #[test]
+    fn meta_keeps_inner_instructions_and_loaded_addresses() {
+        let lookup_key = Pubkey::new_unique();
+        let ui_meta: UiTransactionStatusMeta = serde_json::from_value(serde_json::json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [10, 20],
+            "postBalances": [5, 25],
+        }))
+        .unwrap();
+        let ui_meta = UiTransactionStatusMeta {
+            inner_instructions: OptionSerializer::Some(vec![UiInnerInstructions {
+                index: 1,
+                instructions: vec![UiInstruction::Compiled(UiCompiledInstruction {
+                    program_id_index: 3,
+                    accounts: vec![0, 2],
+                    data: bs58::encode([7u8, 8, 9]).into_string(),
+                    stack_height: Some(2),
+                })],
+            }]),
+            loaded_addresses: OptionSerializer::Some(UiLoadedAddresses {
+                writable: vec![lookup_key.to_string()],
+                readonly: vec![],
+            }),
+            ..ui_meta
+        };
+
+        let meta = proto_meta(&ui_meta);
+        assert_eq!(meta.fee, 5000);
+        assert_eq!(meta.post_balances, vec![5, 25]);
+        assert_eq!(meta.inner_instructions.len(), 1);
+        assert_eq!(meta.inner_instructions[0].instructions[0].data, vec![7, 8, 9]);
+        assert_eq!(meta.inner_instructions[0].instructions[0].program_id_index, 3);
+        assert_eq!(meta.loaded_writable_addresses, vec![lookup_key.to_bytes().to_vec()]);
+        assert!(meta.loaded_readonly_addresses.is_empty());
+    }
+}
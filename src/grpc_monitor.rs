@@ -1,47 +1,813 @@
-use anyhow::{Result, Context};
-use futures::{StreamExt, SinkExt};
+use anyhow::{bail, Result};
+use futures::stream::FuturesOrdered;
+use futures::{Future, Sink, SinkExt, Stream, StreamExt};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::bs58;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use std::collections::HashMap;
-use tracing::{info, error, warn};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, error, warn};
+use tonic::codec::CompressionEncoding;
 use yellowstone_grpc_client::GeyserGrpcClient;
 use yellowstone_grpc_proto::geyser::{
     CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
-    SubscribeRequestFilterTransactions, SubscribeUpdate, SubscribeUpdateTransaction,
+    SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+    SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions, SubscribeUpdate, SubscribeUpdateAccountInfo,
+    SubscribeUpdateTransaction,
 };
+use yellowstone_grpc_proto::geyser::subscribe_request_filter_accounts_filter::Filter as AccountsFilter;
+use yellowstone_grpc_proto::geyser::subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData;
 use yellowstone_grpc_proto::prelude::{Transaction, Message, TransactionStatusMeta};
+use crate::bot_state::BotState;
+use crate::display::{short_signature, ConsoleOutput, Verbosity};
+use crate::error::{MonitorError, ParseError};
+use crate::explorer::Explorer;
+use crate::gap_recovery::{self, GapRecoveryConfig};
+use crate::latency::{LatencyStats, LatencySummary};
+use crate::ledger::SignatureLedger;
+use crate::audit::{AuditLog, AuditSink};
+use crate::cluster::{CandidateStatus, ClusterConfig};
+use crate::parse_failures::{ParseFailure, ParseFailureCapture};
+use crate::parser::TransactionParser;
+use crate::parser::balances::token_balance_changes;
+use crate::price_oracle::PriceOracle;
+use crate::recorder::RecordSink;
+use crate::stream_compat;
+use crate::stream_health::{StreamHealth, StreamHealthConfig};
+use crate::rpc_budget::RpcBudgetConfig;
+use crate::strategy::{Decision, MirrorStrategy, Strategy};
+use crate::types::{DexType, LiquidityEvent, QuoteMints, SkipReason, TradeDetails, TradeDirection, TradeKind, TransferEvent, USDC_MINT, USDT_MINT, WSOL_MINT};
+use crate::parser::lifinity::LIFINITY_V2_PROGRAM;
+use crate::parser::moonshot::MOONSHOT_PROGRAM;
+use crate::parser::phoenix::PHOENIX_PROGRAM;
+use crate::parser::pump::PUMP_PROGRAM;
+use crate::parser::raydium::RAYDIUM_AMM_V4_PROGRAM;
+use crate::token_program::TokenProgram;
+use crate::wallet_balances::{TrackedBalances, WalletBalances};
+use crate::watchlist::mark_from_sol_price;
+use crate::webhook::WebhookSink;
 
 // Common DEX program IDs
 const RAYDIUM_V4: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
 const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
 const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
 
+// 跟单钱包账户订阅的过滤器名称
+const COPY_WALLET_FILTER: &str = "copy_wallet";
+const COPY_WALLET_TOKENS_FILTER: &str = "copy_wallet_tokens";
+const TARGET_WALLET_TOKENS_FILTER: &str = "wallet_tokens";
+// 目标钱包与观察名单的交易过滤器名称
+const TARGET_TX_FILTER: &str = "wallet_tx";
+const WATCH_TX_FILTER: &str = "watch_tx";
+const NEW_POOL_FILTER: &str = "new_pool";
+// 代币账户数据中owner字段的偏移
+const TOKEN_ACCOUNT_OWNER_OFFSET: u64 = 32;
+
+/// 减少需要完整处理的交易数量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionFilterConfig {
+    /// 订阅层面只接收调用了其中任一程序的交易(每个程序一个account_required过滤器)
+    #[serde(default)]
+    pub required_programs: Vec<String>,
+    /// 把有解析器的DEX程序加入required_programs；经未知路由器的成交和转账不再推送，
+    /// 目标钱包的余额变化改由账户订阅跟踪
+    #[serde(default)]
+    pub known_dexes: bool,
+    /// 按程序ID和指令鉴别器预过滤，没有已知成交指令的交易直接跳过(也不做余额推断)
+    #[serde(default)]
+    pub prefilter: bool,
+    /// 同时订阅目标钱包失败的交易，解析其尝试的成交并提示，不跟单
+    #[serde(default)]
+    pub include_failed: bool,
+}
+
+impl TransactionFilterConfig {
+    pub fn required(&self) -> Vec<String> {
+        let mut programs = self.required_programs.clone();
+        if self.known_dexes {
+            for program in TransactionParser::KNOWN_DEX_PROGRAMS {
+                if !programs.iter().any(|p| p == program) {
+                    programs.push(program.to_string());
+                }
+            }
+        }
+        programs
+    }
+}
+
+/// 新池子检测：按DEX程序订阅，识别创建池子/代币的指令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPoolConfig {
+    #[serde(default = "default_pool_programs")]
+    pub programs: Vec<String>,
+    /// 为true时只检测新池子，不再订阅目标钱包和观察名单的交易
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+fn default_pool_programs() -> Vec<String> {
+    vec![PUMP_PROGRAM.to_string(), RAYDIUM_AMM_V4_PROGRAM.to_string()]
+}
+
+/// 消息处理流水线：接收后在阻塞线程池上并行解析，按到达顺序处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// 同时在解析中的消息数上限，达到上限时暂停读取流
+    #[serde(default = "default_parse_workers")]
+    pub parse_workers: usize,
+}
+
+fn default_parse_workers() -> usize {
+    4
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        PipelineConfig { parse_workers: default_parse_workers() }
+    }
+}
+
+/// gRPC通道参数：部分服务商会断开空闲连接，区块等大消息会超过tonic默认的4MB解码上限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// 目前只支持gzip，服务端不支持时仍返回未压缩的消息
+    #[serde(default)]
+    pub compression: Option<StreamCompression>,
+    #[serde(default = "default_keepalive_interval_secs")]
+    pub keepalive_interval_secs: u64,
+    #[serde(default = "default_keepalive_timeout_secs")]
+    pub keepalive_timeout_secs: u64,
+    /// 流上没有消息时也发送HTTP/2 ping
+    #[serde(default = "enabled")]
+    pub keepalive_while_idle: bool,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_max_message_bytes")]
+    pub max_message_bytes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamCompression {
+    Gzip,
+}
+
+fn default_keepalive_interval_secs() -> u64 {
+    10
+}
+
+fn default_keepalive_timeout_secs() -> u64 {
+    20
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_message_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        ChannelConfig {
+            compression: None,
+            keepalive_interval_secs: default_keepalive_interval_secs(),
+            keepalive_timeout_secs: default_keepalive_timeout_secs(),
+            keepalive_while_idle: true,
+            connect_timeout_secs: default_connect_timeout_secs(),
+            max_message_bytes: default_max_message_bytes(),
+        }
+    }
+}
+
+type ParseResult = Result<Option<TradeKind>, ParseError>;
+
+// 已接收的消息，目标钱包的交易附带在工作线程上的解析结果
+struct StagedMessage {
+    msg: SubscribeUpdate,
+    received_at: Instant,
+    received_at_ms: i64,
+    parsed: Option<ParseResult>,
+}
+
+/// 按DEX开关跟单分发；关闭的DEX上的成交仍然记录，供分析使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexSwitches {
+    #[serde(default = "enabled")]
+    pub copy_raydium_amm: bool,
+    #[serde(default = "enabled")]
+    pub copy_pumpfun: bool,
+    #[serde(default = "enabled")]
+    pub copy_moonshot: bool,
+    #[serde(default = "enabled")]
+    pub copy_lifinity: bool,
+    #[serde(default = "enabled")]
+    pub copy_phoenix: bool,
+    /// 由余额变化推断、未识别DEX的成交
+    #[serde(default = "enabled")]
+    pub copy_inferred: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for DexSwitches {
+    fn default() -> Self {
+        DexSwitches {
+            copy_raydium_amm: true,
+            copy_pumpfun: true,
+            copy_moonshot: true,
+            copy_lifinity: true,
+            copy_phoenix: true,
+            copy_inferred: true,
+        }
+    }
+}
+
+impl DexSwitches {
+    pub fn allows(&self, dex: DexType) -> bool {
+        match dex {
+            DexType::Raydium => self.copy_raydium_amm,
+            DexType::PumpFun => self.copy_pumpfun,
+            DexType::Moonshot => self.copy_moonshot,
+            DexType::Lifinity => self.copy_lifinity,
+            DexType::Phoenix => self.copy_phoenix,
+            DexType::Inferred | DexType::Unknown => self.copy_inferred,
+        }
+    }
+}
+
+/// 通过Yellowstone gRPC订阅目标钱包的交易，解析成交并按策略分发给webhook、记录器等下游。
+///
+/// 使用[`GrpcMonitor::builder`]创建，[`GrpcMonitor::start_monitoring`]运行直到出错，断线后自动重连。
 pub struct GrpcMonitor {
     endpoint: String,
     auth_token: Option<String>,
-    target_wallet: Pubkey,
+    target_wallets: Vec<Pubkey>,
+    parser: Arc<TransactionParser>,
+    trade_latency: Mutex<LatencyStats>,
+    price_oracle: Option<Arc<PriceOracle>>,
+    copy_wallet: Option<Pubkey>,
+    copy_wallet_balances: Arc<RwLock<WalletBalances>>,
+    // 各目标钱包的代币余额，由RPC初始化(with_target_balances)，之后由账户更新维护；用于计算卖出比例
+    target_balances: RwLock<HashMap<Pubkey, TrackedBalances>>,
+    webhook: Option<Arc<WebhookSink>>,
+    explorer: Explorer,
+    state: Arc<BotState>,
+    stream_health: StreamHealthConfig,
+    rpc: Option<Arc<RpcClient>>,
+    gap_recovery: Option<GapRecoveryConfig>,
+    ledger: Arc<SignatureLedger>,
+    watch_wallets: Vec<Pubkey>,
+    recorder: Option<RecordSink>,
+    tx_filter: TransactionFilterConfig,
+    quote_mints: QuoteMints,
+    dex_switches: DexSwitches,
+    new_pools: Option<NewPoolConfig>,
+    strategy: Arc<dyn Strategy>,
+    pipeline: PipelineConfig,
+    commitment: CommitmentLevel,
+    output: ConsoleOutput,
+    parse_failures: Option<Arc<ParseFailureCapture>>,
+    audit: Option<AuditSink>,
+    cluster: Option<ClusterConfig>,
+    channel: ChannelConfig,
+}
+
+/// 逐项配置监控器；endpoint和至少一个目标钱包必须设置，其余选项都有默认值
+#[derive(Default)]
+pub struct GrpcMonitorBuilder {
+    endpoint: Option<String>,
+    auth_token: Option<String>,
+    target_wallets: Vec<Pubkey>,
+    target_balances: Vec<(Pubkey, WalletBalances)>,
+    commitment: Option<CommitmentLevel>,
+    state: Option<Arc<BotState>>,
+    price_oracle: Option<Arc<PriceOracle>>,
+    copy_wallet: Option<(Pubkey, WalletBalances)>,
+    watch_wallets: Vec<Pubkey>,
+    recorder: Option<RecordSink>,
+    webhook: Option<Arc<WebhookSink>>,
+    tx_filter: Option<TransactionFilterConfig>,
+    dex_switches: Option<DexSwitches>,
+    quote_mints: Option<QuoteMints>,
+    strategy: Option<Arc<dyn Strategy>>,
+    channel: Option<ChannelConfig>,
+    rpc: Option<Arc<RpcClient>>,
+    rpc_budget: Option<RpcBudgetConfig>,
+    stream_health: Option<StreamHealthConfig>,
+    gap_recovery: Option<GapRecoveryConfig>,
+    ledger: Option<SignatureLedger>,
+    pipeline: Option<PipelineConfig>,
+    explorer: Option<Explorer>,
+    output: Option<ConsoleOutput>,
+    new_pools: Option<NewPoolConfig>,
+    parse_failures: Option<ParseFailureCapture>,
+    audit: Option<AuditLog>,
+    cluster: Option<ClusterConfig>,
+}
+
+impl GrpcMonitorBuilder {
+    /// Yellowstone gRPC服务地址，必须设置
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// 服务商的x-token，不需要认证时为None
+    pub fn auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    /// 添加一个监控并跟单的钱包
+    pub fn target_wallet(mut self, wallet: Pubkey) -> Self {
+        self.target_wallets.push(wallet);
+        self
+    }
+
+    /// 添加多个监控并跟单的钱包
+    pub fn target_wallets(mut self, wallets: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.target_wallets.extend(wallets);
+        self
+    }
+
+    /// 目标钱包代币余额的初始值(应已由RPC获取)，之后由账户更新维护，用于计算卖出比例
+    pub fn target_balances(mut self, wallet: Pubkey, balances: WalletBalances) -> Self {
+        self.target_balances.push((wallet, balances));
+        self
+    }
+
+    /// 订阅的确认级别，默认Confirmed
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = Some(commitment);
+        self
+    }
+
+    /// 使用已有的运行时状态，便于在build之前创建依赖状态的组件(如[`MomentumFilter`](crate::strategy::MomentumFilter))
+    pub fn state(mut self, state: Arc<BotState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// 用于估算成交美元价值的价格预言机
+    pub fn price_oracle(mut self, oracle: Arc<PriceOracle>) -> Self {
+        self.price_oracle = Some(oracle);
+        self
+    }
+
+    /// 同时订阅跟单钱包的账户，在内存中维护其余额；balances应已由RPC初始化
+    pub fn copy_wallet(mut self, wallet: Pubkey, balances: WalletBalances) -> Self {
+        self.copy_wallet = Some((wallet, balances));
+        self
+    }
+
+    /// 只记录交易并模拟盈亏、从不分发的观察名单
+    pub fn watch_wallets(mut self, wallets: Vec<Pubkey>) -> Self {
+        self.watch_wallets = wallets;
+        self
+    }
+
+    /// 每笔解析出的交易都追加到交易记录，写入在后台完成，见[`crate::recorder::TradeRecorder::spawn`]
+    pub fn recorder(mut self, recorder: RecordSink) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// 成交和事件推送到的webhook
+    pub fn notifier(mut self, webhook: Arc<WebhookSink>) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// 订阅层面的交易过滤
+    pub fn transaction_filter(mut self, filter: TransactionFilterConfig) -> Self {
+        self.tx_filter = Some(filter);
+        self
+    }
+
+    /// 按DEX开关分发，关闭的DEX上的成交仍然记录
+    pub fn dex_switches(mut self, switches: DexSwitches) -> Self {
+        self.dex_switches = Some(switches);
+        self
+    }
+
+    /// 决定成交方向的计价代币集合，同时应用到解析器
+    pub fn quote_mints(mut self, quotes: QuoteMints) -> Self {
+        self.quote_mints = Some(quotes);
+        self
+    }
+
+    /// 决定成交是否分发的策略，默认[`MirrorStrategy`]
+    pub fn strategy(mut self, strategy: Arc<dyn Strategy>) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// gRPC通道的压缩、keepalive和消息大小上限
+    pub fn channel(mut self, channel: ChannelConfig) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// RPC用于比较slot滞后和断线补偿
+    pub fn rpc(mut self, rpc: Arc<RpcClient>) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    /// 监控器发起的RPC调用的限速
+    pub fn rpc_budget(mut self, config: RpcBudgetConfig) -> Self {
+        self.rpc_budget = Some(config);
+        self
+    }
+
+    /// 流健康检查：静默超时总是生效；设置了RPC时还会比较slot滞后
+    pub fn stream_health(mut self, config: StreamHealthConfig) -> Self {
+        self.stream_health = Some(config);
+        self
+    }
+
+    /// 重启或重连时通过RPC补回断线期间的交易，需要同时设置RPC
+    pub fn gap_recovery(mut self, config: GapRecoveryConfig) -> Self {
+        self.gap_recovery = Some(config);
+        self
+    }
+
+    /// 替换默认的内存账本，使已处理签名在重启后仍然有效
+    pub fn ledger(mut self, ledger: SignatureLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// 并行解析的工作线程数
+    pub fn pipeline(mut self, config: PipelineConfig) -> Self {
+        self.pipeline = Some(config);
+        self
+    }
+
+    /// 日志和事件中交易/账户链接使用的区块浏览器
+    pub fn explorer(mut self, explorer: Explorer) -> Self {
+        self.explorer = Some(explorer);
+        self
+    }
+
+    /// 控制台输出的详细程度和颜色
+    pub fn output(mut self, output: ConsoleOutput) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// 按DEX程序订阅并识别新建的池子
+    pub fn new_pool_detection(mut self, config: NewPoolConfig) -> Self {
+        self.new_pools = Some(config);
+        self
+    }
+
+    /// 已知DEX的交易解析失败时保存原始指令和日志
+    pub fn parse_failure_capture(mut self, capture: ParseFailureCapture) -> Self {
+        self.parse_failures = Some(capture);
+        self
+    }
+
+    /// 每个分发决策写入HMAC链式审计日志；写入在阻塞线程上完成，build需要在tokio运行时中调用
+    pub fn audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 目标向新地址转出大额SOL时标记为关联钱包候选
+    pub fn cluster_detection(mut self, config: ClusterConfig) -> Self {
+        self.cluster = Some(config);
+        self
+    }
+
+    /// 创建监控器；未设置endpoint或目标钱包时返回错误
+    pub fn build(self) -> Result<GrpcMonitor> {
+        let Some(endpoint) = self.endpoint else { bail!("gRPC endpoint is required") };
+        if self.target_wallets.is_empty() {
+            bail!("at least one target wallet is required");
+        }
+        let mut monitor = GrpcMonitor::new(endpoint, self.auth_token, self.target_wallets[0]);
+        monitor.target_wallets = self.target_wallets;
+        if let Some(state) = self.state {
+            monitor.state = state;
+        }
+        for (wallet, balances) in self.target_balances {
+            monitor = monitor.with_target_balances(wallet, balances);
+        }
+        if let Some(commitment) = self.commitment {
+            monitor.commitment = commitment;
+        }
+        if let Some((wallet, balances)) = self.copy_wallet {
+            monitor.copy_wallet = Some(wallet);
+            monitor.copy_wallet_balances = Arc::new(RwLock::new(balances));
+        }
+        monitor.price_oracle = self.price_oracle;
+        monitor.watch_wallets = self.watch_wallets;
+        monitor.recorder = self.recorder;
+        monitor.webhook = self.webhook;
+        monitor.rpc = self.rpc;
+        monitor.gap_recovery = self.gap_recovery;
+        monitor.new_pools = self.new_pools;
+        monitor.parse_failures = self.parse_failures.map(Arc::new);
+        monitor.audit = self.audit.map(AuditLog::spawn);
+        monitor.cluster = self.cluster;
+        if let Some(quotes) = self.quote_mints {
+            monitor = monitor.with_quote_mints(quotes);
+        }
+        if let Some(strategy) = self.strategy {
+            monitor = monitor.with_strategy(strategy);
+        }
+        if let Some(filter) = self.tx_filter {
+            monitor.tx_filter = filter;
+        }
+        if let Some(switches) = self.dex_switches {
+            monitor.dex_switches = switches;
+        }
+        if let Some(channel) = self.channel {
+            monitor.channel = channel;
+        }
+        if let Some(config) = self.rpc_budget {
+            monitor = monitor.with_rpc_budget(config);
+        }
+        if let Some(config) = self.stream_health {
+            monitor.stream_health = config;
+        }
+        if let Some(ledger) = self.ledger {
+            monitor.ledger = Arc::new(ledger);
+        }
+        if let Some(config) = self.pipeline {
+            monitor.pipeline = config;
+        }
+        if let Some(explorer) = self.explorer {
+            monitor.explorer = explorer;
+        }
+        if let Some(output) = self.output {
+            monitor.output = output;
+        }
+        Ok(monitor)
+    }
 }
 
 impl GrpcMonitor {
+    /// 创建[`GrpcMonitorBuilder`]
+    pub fn builder() -> GrpcMonitorBuilder {
+        GrpcMonitorBuilder::default()
+    }
+
+    /// 只设置连接参数和目标钱包，其余使用默认值；推荐使用[`GrpcMonitor::builder`]
     pub fn new(endpoint: String, auth_token: Option<String>, target_wallet: Pubkey) -> Self {
         GrpcMonitor {
             endpoint,
             auth_token,
-            target_wallet,
+            target_wallets: vec![target_wallet],
+            parser: Arc::new(TransactionParser::new()),
+            trade_latency: Mutex::new(LatencyStats::new()),
+            price_oracle: None,
+            copy_wallet: None,
+            copy_wallet_balances: Arc::new(RwLock::new(WalletBalances::default())),
+            target_balances: RwLock::new(HashMap::new()),
+            webhook: None,
+            explorer: Explorer::default(),
+            state: Arc::new(BotState::new()),
+            stream_health: StreamHealthConfig::default(),
+            rpc: None,
+            gap_recovery: None,
+            ledger: Arc::new(SignatureLedger::in_memory()),
+            watch_wallets: Vec::new(),
+            recorder: None,
+            tx_filter: TransactionFilterConfig::default(),
+            quote_mints: QuoteMints::default(),
+            dex_switches: DexSwitches::default(),
+            new_pools: None,
+            strategy: Arc::new(MirrorStrategy::new(QuoteMints::default())),
+            pipeline: PipelineConfig::default(),
+            commitment: CommitmentLevel::Confirmed,
+            output: ConsoleOutput::default(),
+            parse_failures: None,
+            audit: None,
+            cluster: None,
+            channel: ChannelConfig::default(),
         }
     }
 
+    /// 同[`GrpcMonitor::new`]并设置价格预言机
+    #[deprecated(note = "use GrpcMonitor::builder()")]
+    pub fn new_with_price_oracle(
+        endpoint: String,
+        auth_token: Option<String>,
+        target_wallet: Pubkey,
+        price_oracle: Arc<PriceOracle>,
+    ) -> Self {
+        let mut monitor = GrpcMonitor::new(endpoint, auth_token, target_wallet);
+        monitor.price_oracle = Some(price_oracle);
+        monitor
+    }
+
+    /// 同[`GrpcMonitor::new`]并设置价格预言机和跟单钱包
+    #[deprecated(note = "use GrpcMonitor::builder()")]
+    pub fn new_with_copy_wallet(
+        endpoint: String,
+        auth_token: Option<String>,
+        target_wallet: Pubkey,
+        price_oracle: Arc<PriceOracle>,
+        copy_wallet: Pubkey,
+        balances: WalletBalances,
+    ) -> Self {
+        let mut monitor = GrpcMonitor::new(endpoint, auth_token, target_wallet);
+        monitor.price_oracle = Some(price_oracle);
+        monitor.copy_wallet = Some(copy_wallet);
+        monitor.copy_wallet_balances = Arc::new(RwLock::new(balances));
+        monitor
+    }
+
+    /// 每笔解析出的交易都推送到webhook
+    pub fn with_webhook(mut self, webhook: Arc<WebhookSink>) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// 控制台输出的详细程度和颜色
+    pub fn with_output(mut self, output: ConsoleOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// 已知DEX的交易解析失败时保存原始指令和日志
+    pub fn with_parse_failure_capture(mut self, capture: ParseFailureCapture) -> Self {
+        self.parse_failures = Some(Arc::new(capture));
+        self
+    }
+
+    /// gRPC通道的压缩、keepalive和消息大小上限
+    pub fn with_channel(mut self, channel: ChannelConfig) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// 目标向新地址转出大额SOL时标记为关联钱包候选
+    pub fn with_cluster_detection(mut self, config: ClusterConfig) -> Self {
+        self.cluster = Some(config);
+        self
+    }
+
+    /// 每个分发决策写入HMAC链式审计日志
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit.spawn());
+        self
+    }
+
+    /// 日志和事件中交易/账户链接使用的区块浏览器
+    pub fn with_explorer(mut self, explorer: Explorer) -> Self {
+        self.explorer = explorer;
+        self
+    }
+
+    /// 目标钱包代币余额的初始值(应已由RPC获取)，之后由账户更新维护，用于计算卖出比例
+    pub fn with_target_balances(self, wallet: Pubkey, balances: WalletBalances) -> Self {
+        self.target_balances.write().unwrap_or_else(|e| e.into_inner()).insert(wallet, TrackedBalances::new(balances));
+        self
+    }
+
+    /// RPC用于比较slot滞后和断线补偿
+    pub fn with_rpc(mut self, rpc: Arc<RpcClient>) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    /// 流健康检查：静默超时总是生效；设置了RPC时还会比较slot滞后
+    pub fn with_stream_health(mut self, config: StreamHealthConfig) -> Self {
+        self.stream_health = config;
+        self
+    }
+
+    /// 重启或重连时通过RPC补回断线期间的交易，需要同时设置RPC
+    pub fn with_gap_recovery(mut self, config: GapRecoveryConfig) -> Self {
+        self.gap_recovery = Some(config);
+        self
+    }
+
+    /// 替换默认的内存账本，使已处理签名在重启后仍然有效
+    pub fn with_ledger(mut self, ledger: SignatureLedger) -> Self {
+        self.ledger = Arc::new(ledger);
+        self
+    }
+
+    /// 观察名单中的钱包只记录交易并模拟盈亏，从不分发
+    pub fn with_watch_wallets(mut self, wallets: Vec<Pubkey>) -> Self {
+        self.watch_wallets = wallets;
+        self
+    }
+
+    /// 每笔解析出的交易都追加到交易记录文件，写入在后台完成
+    pub fn with_recorder(mut self, recorder: RecordSink) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// 订阅层面的交易过滤，见[`TransactionFilterConfig`]
+    pub fn with_transaction_filter(mut self, filter: TransactionFilterConfig) -> Self {
+        self.tx_filter = filter;
+        self
+    }
+
+    /// 监控器发起的RPC调用的限速
+    pub fn with_rpc_budget(self, config: RpcBudgetConfig) -> Self {
+        self.state.rpc_budget().configure(config);
+        self
+    }
+
+    /// 按DEX程序订阅并识别新建的池子
+    pub fn with_new_pool_detection(mut self, config: NewPoolConfig) -> Self {
+        self.new_pools = Some(config);
+        self
+    }
+
+    /// 按DEX开关分发，关闭的DEX上的成交仍然记录
+    pub fn with_dex_switches(mut self, switches: DexSwitches) -> Self {
+        self.dex_switches = switches;
+        self
+    }
+
+    /// 并行解析的工作线程数
+    pub fn with_pipeline(mut self, config: PipelineConfig) -> Self {
+        self.pipeline = config;
+        self
+    }
+
+    /// 决定成交是否分发的策略，默认[`MirrorStrategy`]
+    pub fn with_strategy(mut self, strategy: Arc<dyn Strategy>) -> Self {
+        info!("Using {} strategy", strategy.name());
+        self.strategy = strategy;
+        self
+    }
+
+    /// 决定成交方向的计价代币集合，同时应用到解析器
+    pub fn with_quote_mints(mut self, quotes: QuoteMints) -> Self {
+        self.parser = Arc::new(TransactionParser::new().with_quote_mints(quotes.clone()));
+        self.quote_mints = quotes;
+        self
+    }
+
+    /// 跟单钱包余额的共享视图
+    pub fn copy_wallet_balances(&self) -> Arc<RwLock<WalletBalances>> {
+        Arc::clone(&self.copy_wallet_balances)
+    }
+
+    /// 与API等组件共享的运行时状态
+    pub fn state(&self) -> Arc<BotState> {
+        Arc::clone(&self.state)
+    }
+
+    /// 已处理交易签名的账本，用于去重
+    pub fn ledger(&self) -> Arc<SignatureLedger> {
+        Arc::clone(&self.ledger)
+    }
+
+    /// 连接并处理订阅流，断线或流停滞时自动重连；只在无法恢复的错误时返回
     pub async fn start_monitoring(&self) -> Result<()> {
-        info!("Starting gRPC monitoring service, target wallet: {}", self.target_wallet);
+        let targets: Vec<String> = self.target_wallets.iter().map(|w| w.to_string()).collect();
+        info!("Starting gRPC monitoring service, target wallets: {}", targets.join(", "));
         info!("Connecting to gRPC endpoint: {}", self.endpoint);
         
+        if let Some(rpc) = &self.rpc {
+            let rpc = Arc::clone(rpc);
+            let state = Arc::clone(&self.state);
+            let interval = StreamHealth::new(self.stream_health.clone()).check_interval();
+            tokio::spawn(async move {
+                loop {
+                    state.rpc_budget().acquire("getSlot").await;
+                    match rpc.get_slot_with_commitment(CommitmentConfig::confirmed()).await {
+                        Ok(slot) => state.set_rpc_slot(slot),
+                        Err(e) => warn!("RPC getSlot failed: {}", e),
+                    }
+                    tokio::time::sleep(interval).await;
+                }
+            });
+        }
+        
         loop {
-            match self.monitor_loop().await {
+            self.recover_gap().await;
+            let result = self.monitor_loop().await;
+            self.state.set_connected(false);
+            match result {
                 Ok(_) => {
                     warn!("Monitoring loop ended, preparing to restart...");
                 }
+                // 连接本身正常，只是流停滞，立即重连
+                Err(MonitorError::Stale(problem)) => {
+                    warn!("Reconnecting immediately after stale stream: {}", problem);
+                    continue;
+                }
                 Err(e) => {
-                    error!("Monitoring error: {:?}", e);
+                    error!("Monitoring error: {}", e);
                 }
             }
             
@@ -50,157 +816,660 @@ impl GrpcMonitor {
         }
     }
 
-    async fn monitor_loop(&self) -> Result<()> {
-        let mut client = GeyserGrpcClient::build_from_shared(self.endpoint.clone())?
-            .connect()
-            .await
-            .context("Unable to connect to gRPC service")?;
+    // 从上次处理到的slot开始补回目标钱包的交易
+    async fn recover_gap(&self) {
+        let (config, rpc) = match (&self.gap_recovery, &self.rpc) {
+            (Some(config), Some(rpc)) => (config, rpc),
+            _ => return,
+        };
+        let since_slot = match self.state.stream_slot() {
+            0 => match gap_recovery::load_checkpoint(&config.checkpoint_file) {
+                Some(slot) => slot,
+                None => {
+                    info!("No slot checkpoint found, skipping gap recovery");
+                    return;
+                }
+            },
+            slot => slot,
+        };
+        
+        info!("Recovering missed transactions since slot {}...", since_slot);
+        for wallet in &self.target_wallets {
+            self.recover_wallet_gap(config, rpc, wallet, since_slot).await;
+        }
+    }
+
+    async fn recover_wallet_gap(&self, config: &GapRecoveryConfig, rpc: &RpcClient, wallet: &Pubkey, since_slot: u64) {
+        let missed = match gap_recovery::fetch_missed_transactions(rpc, self.state.rpc_budget(), wallet, since_slot, config.max_signatures).await {
+            Ok(missed) => missed,
+            Err(e) => {
+                warn!("Gap recovery failed for {}: {:?}", wallet, e);
+                return;
+            }
+        };
+        
+        let mut recovered = 0;
+        for tx in &missed {
+            let parsed = self.parser.classify(&tx.signature, &tx.transaction, tx.meta.as_ref(), wallet);
+            match parsed {
+                Ok(Some(TradeKind::Liquidity(event))) => self.skip_liquidity(&event),
+                Ok(Some(TradeKind::Transfer(transfer))) => self.skip_transfer(&transfer),
+                Ok(Some(TradeKind::Swap(mut trade))) => {
+                    if !self.ledger.claim(&trade.signature) {
+                        continue;
+                    }
+                    trade.timeline.slot = tx.slot;
+                    self.value_trade_in_usd(&mut trade);
+                    info!("Recovered trade {} at slot {}: {} {} -> {} {}",
+                        trade.signature, tx.slot, trade.amount_in, trade.input_token, trade.amount_out, trade.output_token);
+                    let decision = if config.copy_missed { self.decide(&trade) } else { Decision::Skip(SkipReason::RecoveredOnly) };
+                    match decision {
+                        Decision::Skip(reason) => {
+                            self.state.record_skip(&reason);
+                            trade.skip_reason = Some(reason);
+                        }
+                        Decision::Copy => {
+                            if let Some(webhook) = &self.webhook {
+                                webhook.send_trade(&trade);
+                            }
+                        }
+                    }
+                    self.audit(&trade);
+                    self.state.digest().record(&trade, trade.skip_reason.is_none());
+                    self.state.target_pnl().record(&trade, self.mark_price(&trade));
+                    self.record_trade(&trade);
+                    self.state.push_trade(trade);
+                    recovered += 1;
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to parse recovered transaction {}: {}", tx.signature, e),
+            }
+        }
+        info!("Gap recovery done for {}: {} transactions, {} trades{}", wallet, missed.len(), recovered,
+            if config.copy_missed { "" } else { " (recorded only)" });
+        
+        if let Some(last) = missed.last() {
+            self.state.set_stream_slot(last.slot);
+        }
+    }
+
+    async fn monitor_loop(&self) -> Result<(), MonitorError> {
+        let channel = &self.channel;
+        let mut builder = GeyserGrpcClient::build_from_shared(self.endpoint.clone())?
+            .x_token(self.auth_token.clone())?
+            .connect_timeout(Duration::from_secs(channel.connect_timeout_secs))
+            .http2_keep_alive_interval(Duration::from_secs(channel.keepalive_interval_secs))
+            .keep_alive_timeout(Duration::from_secs(channel.keepalive_timeout_secs))
+            .keep_alive_while_idle(channel.keepalive_while_idle)
+            .max_decoding_message_size(channel.max_message_bytes);
+        if channel.compression == Some(StreamCompression::Gzip) {
+            builder = builder.accept_compressed(CompressionEncoding::Gzip);
+        }
+        let mut client = builder.connect().await?;
         
         info!("Connected to gRPC service, preparing to subscribe...");
         
+        // 握手：记录服务端版本，不支持GetVersion的服务计入兼容性问题
+        match client.get_version().await {
+            Ok(version) => {
+                info!("gRPC provider version: {}", version.version);
+                self.state.stream_compat().set_provider_version(version.version);
+            }
+            Err(e) => {
+                warn!("gRPC provider did not answer GetVersion: {}", e);
+                self.state.stream_compat().record("get_version_unsupported");
+            }
+        }
+        
+        // 优先使用双向流，运行中监控的钱包变化时可以在同一条流上重新发送订阅
+        info!("Sending subscription request...");
+        match client.subscribe().await {
+            Ok((mut sender, receiver)) => {
+                if let Err(e) = sender.send(self.subscribe_request()).await {
+                    error!("Failed to send subscription request: {:?}", e);
+                    return Err(MonitorError::SubscribeRequest);
+                }
+                info!("Subscription successful, starting to receive data...");
+                self.state.set_connected(true);
+                self.consume_stream(receiver, Some(sender)).await?;
+            }
+            Err(e) => {
+                error!("Subscription failed: {:?}", e);
+                
+                info!("Trying alternative subscription method (filter changes apply on reconnect)...");
+                let stream = client.subscribe_once(self.subscribe_request()).await?;
+                info!("Alternative subscription successful, starting to receive data...");
+                self.state.set_connected(true);
+                self.consume_stream(stream, None::<futures::channel::mpsc::Sender<SubscribeRequest>>).await?;
+            }
+        }
+        
+        Ok(())
+    }
+
+    // 按当前监控的钱包构造订阅请求
+    fn subscribe_request(&self) -> SubscribeRequest {
         let mut accounts = HashMap::new();
         accounts.insert(
             "wallet".to_string(),
             SubscribeRequestFilterAccounts {
-                account: vec![self.target_wallet.to_string()],
+                account: self.target_wallets.iter().map(|w| w.to_string()).collect(),
                 owner: vec![],
                 filters: vec![],
             },
         );
 
+        if let Some(copy_wallet) = &self.copy_wallet {
+            accounts.insert(
+                COPY_WALLET_FILTER.to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: vec![copy_wallet.to_string()],
+                    owner: vec![],
+                    filters: vec![],
+                },
+            );
+            accounts.insert(COPY_WALLET_TOKENS_FILTER.to_string(), token_accounts_filter(copy_wallet));
+        }
+
+        // 目标钱包的代币账户：交易之外的转入(转账、空投、未知路由器)也能反映到余额中，
+        // 交易订阅按程序预过滤时这是唯一能看到这些变化的途径
+        for wallet in &self.target_wallets {
+            accounts.insert(format!("{}:{}", TARGET_WALLET_TOKENS_FILTER, wallet), token_accounts_filter(wallet));
+        }
+        let required = self.tx_filter.required();
+
+        // account_required要求全部命中，程序白名单需要每个程序单独一个过滤器
         let mut transactions = HashMap::new();
-        transactions.insert(
-            "wallet_tx".to_string(),
-            SubscribeRequestFilterTransactions {
-                vote: Some(false),
-                failed: Some(false),
-                signature: None,
-                account_include: vec![self.target_wallet.to_string()],
-                account_exclude: vec![],
-                account_required: vec![],
+        let target_filter = |required: Vec<String>| SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: if self.tx_filter.include_failed { None } else { Some(false) },
+            signature: None,
+            account_include: self.target_wallets.iter().map(|w| w.to_string()).collect(),
+            account_exclude: vec![],
+            account_required: required,
+        };
+        // 只检测新池子时不订阅钱包交易
+        let exclusive = self.new_pools.as_ref().is_some_and(|c| c.exclusive);
+        if !exclusive && required.is_empty() {
+            transactions.insert(TARGET_TX_FILTER.to_string(), target_filter(vec![]));
+        } else if !exclusive {
+            for program in &required {
+                transactions.insert(format!("{}:{}", TARGET_TX_FILTER, program), target_filter(vec![program.clone()]));
+            }
+        }
+
+        if let Some(config) = &self.new_pools {
+            transactions.insert(
+                NEW_POOL_FILTER.to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: Some(false),
+                    failed: Some(false),
+                    signature: None,
+                    account_include: config.programs.clone(),
+                    account_exclude: vec![],
+                    account_required: vec![],
+                },
+            );
+        }
+
+        // 观察名单加上已确认的关联钱包候选
+        let mut watch_wallets = self.watch_wallets.clone();
+        watch_wallets.extend(self.state.cluster().watched().into_iter().filter(|w| !self.watch_wallets.contains(w)));
+        if !watch_wallets.is_empty() && !exclusive {
+            transactions.insert(
+                WATCH_TX_FILTER.to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: Some(false),
+                    failed: Some(false),
+                    signature: None,
+                    account_include: watch_wallets.iter().map(|w| w.to_string()).collect(),
+                    account_exclude: vec![],
+                    account_required: vec![],
+                },
+            );
+        }
+
+        // 订阅slot更新，使流在目标钱包不活跃时也有持续的数据，便于发现停滞
+        let mut slots = HashMap::new();
+        slots.insert(
+            "slots".to_string(),
+            SubscribeRequestFilterSlots {
+                filter_by_commitment: Some(true),
             },
         );
 
-        let request = SubscribeRequest {
+        SubscribeRequest {
             accounts,
-            slots: HashMap::new(),
+            slots,
             transactions,
             transactions_status: HashMap::new(),
             blocks: HashMap::new(),
             blocks_meta: HashMap::new(),
             entry: HashMap::new(),
-            commitment: Some(CommitmentLevel::Confirmed as i32),
+            commitment: Some(self.commitment as i32),
             accounts_data_slice: vec![],
             ping: None,
-        };
+        }
+    }
+
+    // 读取订阅流直到结束；流静默或slot落后RPC过多时返回错误以触发重连。
+    // 有updates时，监控的钱包变化后在同一条流上重新发送订阅，不断开连接
+    async fn consume_stream<S, E, U>(&self, stream: S, mut updates: Option<U>) -> Result<(), MonitorError>
+    where
+        S: Stream<Item = std::result::Result<SubscribeUpdate, E>>,
+        E: std::fmt::Debug,
+        U: Sink<SubscribeRequest> + Unpin,
+        U::Error: std::fmt::Debug,
+    {
+        tokio::pin!(stream);
+        let mut health = StreamHealth::new(self.stream_health.clone());
+        let mut ticker = tokio::time::interval(health.check_interval());
+        ticker.tick().await;
+        // 解析中的消息，按接收顺序出队
+        let mut staged = FuturesOrdered::new();
+        let capacity = self.pipeline.parse_workers.max(1);
         
-        info!("Sending subscription request...");
-        match client.subscribe_once(request.clone()).await {
-            Ok(mut stream) => {
-                info!("Subscription successful, starting to receive data...");
-                
-                while let Some(message) = stream.next().await {
-                    match message {
-                        Ok(msg) => {
-                            self.process_message(msg).await;
-                        }
-                        Err(e) => {
-                            error!("Message reception error: {:?}", e);
-                            return Err(anyhow::anyhow!("Stream error: {:?}", e));
+        loop {
+            tokio::select! {
+                message = stream.next(), if staged.len() < capacity => match message {
+                    Some(Ok(msg)) => {
+                        health.record_update(update_slot(&msg));
+                        if let Some(slot) = health.last_slot() {
+                            self.state.set_stream_slot(slot);
                         }
+                        staged.push_back(self.stage(msg));
+                        self.state.set_pipeline_depth(staged.len());
                     }
-                }
-            }
-            Err(e) => {
-                error!("Subscription failed: {:?}", e);
-                
-                info!("Trying alternative subscription method...");
-                match client.subscribe().await {
-                    Ok((mut sender, mut receiver)) => {
-                        info!("Alternative subscription successful, sending subscription request...");
-                        
-                        if let Err(e) = sender.send(request).await {
-                            error!("Failed to send subscription request: {:?}", e);
-                            return Err(anyhow::anyhow!("Failed to send subscription request"));
+                    Some(Err(e)) => {
+                        error!("Message reception error: {:?}", e);
+                        return Err(MonitorError::Stream(format!("{:?}", e)));
+                    }
+                    None => {
+                        while let Some(message) = staged.next().await {
+                            self.process_staged(message);
                         }
-                        
-                        info!("Starting to receive data...");
-                        
-                        while let Some(message) = receiver.next().await {
-                            match message {
-                                Ok(msg) => {
-                                    self.process_message(msg).await;
-                                }
-                                Err(e) => {
-                                    error!("Message reception error: {:?}", e);
-                                    return Err(anyhow::anyhow!("Stream error: {:?}", e));
-                                }
-                            }
+                        self.state.set_pipeline_depth(0);
+                        return Ok(());
+                    }
+                },
+                Some(message) = staged.next(), if !staged.is_empty() => {
+                    self.process_staged(message);
+                    self.state.set_pipeline_depth(staged.len());
+                },
+                _ = self.state.subscription_changed(), if updates.is_some() => {
+                    let Some(sender) = updates.as_mut() else { continue };
+                    info!("Monitored wallets changed, resubscribing over the existing stream");
+                    if let Err(e) = sender.send(self.subscribe_request()).await {
+                        error!("Failed to send updated subscription request: {:?}", e);
+                        return Err(MonitorError::SubscribeRequest);
+                    }
+                },
+                _ = ticker.tick() => {
+                    if let (Some(config), Some(slot)) = (&self.gap_recovery, health.last_slot()) {
+                        if let Err(e) = gap_recovery::save_checkpoint(&config.checkpoint_file, slot).await {
+                            warn!("Failed to save slot checkpoint: {:?}", e);
                         }
                     }
-                    Err(e2) => {
-                        error!("Alternative subscription also failed: {:?}", e2);
-                        return Err(anyhow::anyhow!("All subscription methods failed"));
+                    let rpc_slot = self.state.rpc_slot();
+                    let problem = health.check_silence(Instant::now())
+                        .or_else(|| if rpc_slot > 0 { health.check_lag(rpc_slot) } else { None });
+                    if let Some(problem) = problem {
+                        self.state.record_stale_reconnect();
+                        warn!("gRPC stream unhealthy ({}), reconnecting", problem);
+                        return Err(MonitorError::Stale(problem));
                     }
                 }
             }
         }
-        
-        Ok(())
     }
 
-    async fn process_message(&self, msg: SubscribeUpdate) {
-        if let Some(update_oneof) = &msg.update_oneof {
-            use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
-            
-            match update_oneof {
-                UpdateOneof::Transaction(tx_update) => {
-                    self.process_transaction(tx_update);
+    // 目标钱包的交易交给阻塞线程池解析，其余消息直接进入队列
+    fn stage(&self, msg: SubscribeUpdate) -> impl Future<Output = Option<StagedMessage>> {
+        let received_at = Instant::now();
+        let received_at_ms = chrono::Utc::now().timestamp_millis();
+        let parser = self.parser.clone();
+        let target_wallets = self.target_wallets.clone();
+        let prefilter = self.tx_filter.prefilter;
+        async move {
+            if !is_target_transaction(&msg) {
+                return Some(StagedMessage { msg, received_at, received_at_ms, parsed: None });
+            }
+            let parsed = tokio::task::spawn_blocking(move || {
+                let parsed = parse_target_transaction(&parser, &target_wallets, prefilter, &msg);
+                (msg, parsed)
+            }).await;
+            match parsed {
+                Ok((msg, parsed)) => Some(StagedMessage { msg, received_at, received_at_ms, parsed }),
+                Err(e) => {
+                    error!("Parse worker failed: {:?}", e);
+                    None
                 }
-                UpdateOneof::Account(account) => {
+            }
+        }
+    }
+
+    fn process_staged(&self, message: Option<StagedMessage>) {
+        if let Some(message) = message {
+            self.process_message(message.msg, message.parsed, message.received_at, message.received_at_ms);
+        }
+    }
+
+    fn process_message(&self, msg: SubscribeUpdate, parsed: Option<ParseResult>, received_at: Instant, received_at_ms: i64) {
+        use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+        
+        // 较新的proto新增的更新类型在这里解码为空
+        let Some(update_oneof) = &msg.update_oneof else {
+            self.state.stream_compat().record("unknown_update");
+            return;
+        };
+        
+        match update_oneof {
+            UpdateOneof::Transaction(tx_update) => {
+                if let Some(issue) = stream_compat::missing_transaction_field(tx_update) {
+                    self.state.stream_compat().record(issue);
+                    return;
+                }
+                // 一笔交易可能同时命中多个过滤器
+                if msg.filters.iter().any(|f| f == NEW_POOL_FILTER) {
+                    self.process_new_pool_transaction(tx_update);
+                }
+                if msg.filters.iter().any(|f| f.starts_with(TARGET_TX_FILTER)) && is_failed(tx_update) {
+                    self.process_failed_transaction(tx_update);
+                } else if msg.filters.iter().any(|f| f.starts_with(TARGET_TX_FILTER)) {
+                    match parsed {
+                        Some(parsed) => self.process_transaction(tx_update, parsed, received_at, received_at_ms),
+                        None => debug!("Skipping target transaction without known trade instructions"),
+                    }
+                } else if msg.filters.iter().any(|f| f == WATCH_TX_FILTER) {
+                    self.process_watched_transaction(tx_update);
+                }
+            }
+            UpdateOneof::Account(account) => {
+                let is_copy_wallet = msg.filters.iter()
+                    .any(|f| f == COPY_WALLET_FILTER || f == COPY_WALLET_TOKENS_FILTER);
+                if is_copy_wallet {
+                    if let Some(acc) = &account.account {
+                        self.update_copy_wallet_balances(acc);
+                    }
+                } else if let Some(wallet) = msg.filters.iter().find_map(|f| target_tokens_wallet(f)) {
                     if let Some(acc) = &account.account {
-                        let sol = acc.lamports as f64 / 1_000_000_000.0;
-                        info!("=== Account Update ===");
-                        info!("Balance: {} SOL", sol);
+                        self.update_target_balances(&wallet, acc, account.slot);
                     }
+                } else if let Some(acc) = &account.account {
+                    let sol = acc.lamports as f64 / 1_000_000_000.0;
+                    info!("=== Account Update ===");
+                    info!("Balance: {} SOL", sol);
                 }
-                UpdateOneof::Ping(_) => {
-                    // Ignore ping messages
+            }
+            other => {
+                // slot已在consume_stream中记录，未订阅的类型计入兼容性问题
+                if let Some(issue) = stream_compat::unexpected_update(other) {
+                    self.state.stream_compat().record(issue);
                 }
-                _ => {
-                    // Ignore other updates
+            }
+        }
+    }
+
+    fn update_copy_wallet_balances(&self, acc: &SubscribeUpdateAccountInfo) {
+        let (Some(copy_wallet), Ok(pubkey), Ok(owner)) = (
+            self.copy_wallet,
+            Pubkey::try_from(acc.pubkey.as_slice()),
+            Pubkey::try_from(acc.owner.as_slice()),
+        ) else {
+            return;
+        };
+        
+        let mut balances = self.copy_wallet_balances.write().unwrap_or_else(|e| e.into_inner());
+        if balances.apply_account_update(&copy_wallet, &pubkey, &owner, acc.lamports, &acc.data) {
+            info!("Copy wallet balances: {:.6} SOL, {:.6} WSOL, {} token accounts",
+                balances.sol_lamports as f64 / 1_000_000_000.0,
+                balances.wsol_lamports() as f64 / 1_000_000_000.0,
+                balances.token_accounts.len());
+        }
+    }
+
+    fn update_target_balances(&self, wallet: &Pubkey, acc: &SubscribeUpdateAccountInfo, slot: u64) {
+        let (Ok(pubkey), Ok(owner)) = (Pubkey::try_from(acc.pubkey.as_slice()), Pubkey::try_from(acc.owner.as_slice())) else {
+            return;
+        };
+        let mut targets = self.target_balances.write().unwrap_or_else(|e| e.into_inner());
+        let balances = targets.entry(*wallet).or_default();
+        if !balances.apply_account_update(wallet, &pubkey, &owner, acc.lamports, &acc.data, slot) {
+            return;
+        }
+        match balances.balances.token_accounts.get(&pubkey) {
+            Some(token) => debug!("{} token balance: {} of {}", self.output.wallet_prefix(wallet), token.amount, token.mint),
+            None => debug!("{} closed token account {}", self.output.wallet_prefix(wallet), pubkey),
+        }
+    }
+
+    // 卖出/互换时目标卖出的代币占其卖出前持仓的比例
+    fn target_sell_fraction(&self, trade: &TradeDetails) -> Option<f64> {
+        match trade.direction(&self.quote_mints) {
+            TradeDirection::Sell | TradeDirection::Swap => self.target_balances.read().unwrap_or_else(|e| e.into_inner())
+                .get(&trade.wallet)?
+                .sell_fraction(&trade.input_token, trade.amount_in, trade.timeline.slot),
+            TradeDirection::Buy => None,
+        }
+    }
+
+    // 暂停、DEX开关之后由策略决定是否分发；暂停时仍记录交易
+    fn decide(&self, trade: &TradeDetails) -> Decision {
+        if self.state.is_paused() {
+            return Decision::Skip(SkipReason::Paused);
+        }
+        if !self.dex_switches.allows(trade.dex) {
+            return Decision::Skip(SkipReason::DexDisabled { dex: trade.dex });
+        }
+        let portfolio = self.copy_wallet_balances.read().unwrap_or_else(|e| e.into_inner());
+        self.strategy.decide(trade, &portfolio)
+    }
+
+    fn process_new_pool_transaction(&self, tx_update: &SubscribeUpdateTransaction) {
+        let Some(tx_info) = &tx_update.transaction else { return };
+        let Some(tx) = &tx_info.transaction else { return };
+        let signature = bs58::encode(&tx_info.signature).into_string();
+        
+        for pool in self.parser.new_pools(&signature, tx, tx_info.meta.as_ref()) {
+            info!("New {} pool {} at slot {}: {} / {} by {}{}",
+                pool.dex, self.explorer.account_url(&pool.pool), tx_update.slot, pool.base_mint, pool.quote_mint, pool.creator,
+                pool.symbol.as_deref().map(|s| format!(" ({})", s)).unwrap_or_default());
+            // 毕业迁移：之后该代币的成交都在新的Raydium池子上，持仓按代币记录因此无需迁移
+            if let Some(mint) = pool.pump_migration() {
+                info!("Pump.fun token {} graduated to Raydium pool {}", mint, pool.pool);
+                self.state.record_migration(mint, pool.pool);
+                if let Some(webhook) = &self.webhook {
+                    webhook.send_migration(&pool);
                 }
+            } else if let Some(webhook) = &self.webhook {
+                webhook.send_new_pool(&pool);
             }
         }
     }
 
-    fn process_transaction(&self, tx_update: &SubscribeUpdateTransaction) {
-        if let Some(transaction) = &tx_update.transaction {
-            let signature = if transaction.signatures.len() > 0 {
-                bs58::encode(&transaction.signatures[0]).into_string()
+    // 目标钱包失败的交易：解析尝试的成交并通知，不记录为成交也不跟单
+    fn process_failed_transaction(&self, tx_update: &SubscribeUpdateTransaction) {
+        let Some(tx_info) = &tx_update.transaction else { return };
+        let (Some(tx), Some(meta)) = (&tx_info.transaction, &tx_info.meta) else { return };
+        let signature = bs58::encode(&tx_info.signature).into_string();
+        
+        let Some(attempt) = self.parser.parse_attempt(&signature, tx, meta) else {
+            debug!("Failed target transaction {} without a known trade instruction", signature);
+            return;
+        };
+        self.state.record_failed_attempt();
+        info!("Target trade attempt failed ({}) at slot {}: {} up to {} {} -> {} {} ({})",
+            attempt.dex, tx_update.slot, attempt.error, attempt.amount_in, attempt.input_token,
+            attempt.amount_out, attempt.output_token, self.explorer.tx_url(&signature));
+        if let Some(webhook) = &self.webhook {
+            webhook.send_failed_attempt(&attempt);
+        }
+    }
+
+    // 成交代币的参考价格(lamports/最小单位)，优先用监控到的成交价，没有时再取预言机缓存
+    fn mark_price(&self, trade: &TradeDetails) -> Option<f64> {
+        let token = match trade.direction(&self.quote_mints) {
+            TradeDirection::Buy => trade.output_token,
+            TradeDirection::Sell | TradeDirection::Swap => trade.input_token,
+        };
+        let observed = self.state.swap_prices().price(&token)
+            .map(|price| mark_from_sol_price(price.price_sol, price.decimals));
+        observed.or_else(|| match (&self.price_oracle, trade.token_decimals) {
+            (Some(oracle), Some(decimals)) => {
+                let cached = oracle.cached_price(&token.to_string()).and_then(|p| p.price_sol);
+                if cached.is_none() {
+                    oracle.refresh_in_background(&token.to_string());
+                }
+                cached.map(|price_sol| mark_from_sol_price(price_sol, decimals))
+            }
+            _ => None,
+        })
+    }
+
+    // 观察名单钱包的交易：记录到观察名单
+    fn process_watched_transaction(&self, tx_update: &SubscribeUpdateTransaction) {
+        let Some(tx_info) = &tx_update.transaction else { return };
+        let Some(tx) = &tx_info.transaction else { return };
+        let signature = bs58::encode(&tx_info.signature).into_string();
+        
+        let parsed = self.parser.parse_transaction(&signature, tx, tx_info.meta.as_ref());
+        if let Ok(Some(trade)) = &parsed {
+            self.state.swap_prices().observe(trade);
+        }
+        match parsed {
+            Ok(Some(trade)) if self.watch_wallets.contains(&trade.wallet) || self.state.cluster().watched().contains(&trade.wallet) => {
+                info!("Watchlist trade {}", self.output.trade_line(&trade, &self.quote_mints));
+                if let Some(candidate) = self.state.cluster().record_trade(&trade.wallet) {
+                    info!("Cluster candidate {} (funded by {}) started trading", candidate.wallet, candidate.funded_by);
+                    if let Some(webhook) = &self.webhook {
+                        webhook.send_cluster_trading(&candidate, &trade.signature);
+                    }
+                }
+                self.state.watchlist().record(&trade, self.mark_price(&trade));
+                self.record_trade(&trade);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Failed to parse watchlist transaction {}: {}", signature, e);
+                let dex = self.identify_dex(tx).unwrap_or_else(|| "Unknown".to_string());
+                self.capture_parse_failure(tx_update, &signature, &dex, Some(e.to_string()));
+            }
+        }
+    }
+
+    fn process_transaction(&self, tx_update: &SubscribeUpdateTransaction, parsed: ParseResult, received_at: Instant, received_at_ms: i64) {
+        if let Some(tx_info) = &tx_update.transaction {
+            let signature = if !tx_info.signature.is_empty() {
+                bs58::encode(&tx_info.signature).into_string()
             } else {
                 "Unknown".to_string()
             };
+            let verbose = self.output.is_verbose();
+            let dex_name = tx_info.transaction.as_ref().and_then(|tx| self.identify_dex(tx));
+            let target_wallet = tx_info.transaction.as_ref()
+                .map_or(self.target_wallets[0], |tx| transaction_target(&self.target_wallets, tx));
             
-            info!("╔════════════════ 🔄 New Transaction Detected ════════════════╗");
-            info!("║ Signature: {}...{}", &signature[..8], &signature[signature.len()-8..]);
-            info!("║ Link: https://solscan.io/tx/{}", signature);
+            if verbose {
+                info!("╔════════════════ 🔄 New Transaction Detected ════════════════╗");
+                info!("║ Signature: {}", short_signature(&signature));
+                info!("║ Link: {}", self.explorer.tx_url(&signature));
+                if let Some(dex_name) = &dex_name {
+                    info!("║ DEX Platform: {}", dex_name);
+                }
+            }
             
-            // Identify DEX
-            if let Some(dex_name) = self.identify_dex(transaction) {
-                info!("║ DEX Platform: {}", dex_name);
+            match (&parsed, &dex_name) {
+                (Ok(None), Some(dex)) => self.capture_parse_failure(tx_update, &signature, dex, None),
+                (Err(e), dex) => self.capture_parse_failure(tx_update, &signature, dex.as_deref().unwrap_or("Unknown"), Some(e.to_string())),
+                _ => {}
+            }
+            
+            match parsed {
+                Ok(Some(TradeKind::Liquidity(event))) => self.skip_liquidity(&event),
+                Ok(Some(TradeKind::Transfer(transfer))) => self.skip_transfer(&transfer),
+                Ok(Some(_)) if !self.ledger.claim(&signature) => {
+                    if verbose {
+                        info!("║ Trade already processed, skipping duplicate delivery");
+                    } else {
+                        debug!("Trade {} already processed, skipping duplicate delivery", signature);
+                    }
+                }
+                Ok(Some(TradeKind::Swap(mut trade))) => {
+                    let parse_elapsed = received_at.elapsed();
+                    trade.timeline.slot = tx_update.slot;
+                    trade.timeline.received_at_ms = received_at_ms;
+                    trade.timeline.parsed_at_ms = chrono::Utc::now().timestamp_millis();
+                    trade.timeline.parse_micros = parse_elapsed.as_micros() as u64;
+                    trade.sell_fraction = self.target_sell_fraction(&trade);
+                    
+                    if verbose {
+                        info!("║ ---- Parsed Trade ({} {}) ----", trade.dex, trade.direction(&self.quote_mints));
+                        info!("║ Wallet: {}", trade.wallet);
+                        info!("║ {} {} -> {} {}", trade.amount_in, trade.input_token, trade.amount_out, trade.output_token);
+                        if let Some(fraction) = trade.sell_fraction {
+                            info!("║ Sold {:.1}% of position", fraction * 100.0);
+                        }
+                        info!("║ Price: {:.12}", trade.price);
+                        info!("║ Cost: {} lamports (base {} + priority {} + tip {})",
+                            trade.costs.total(), trade.costs.base_fee, trade.costs.priority_fee, trade.costs.tip);
+                    }
+                    self.state.swap_prices().observe(&trade);
+                    self.value_trade_in_usd(&mut trade);
+                    self.log_market_price(&trade);
+                    
+                    let processed_elapsed = received_at.elapsed();
+                    trade.timeline.processed_micros = processed_elapsed.as_micros() as u64;
+                    self.log_latency(&trade, processed_elapsed);
+                    
+                    // 暂停时仍记录交易，但不再分发给下游
+                    match self.decide(&trade) {
+                        Decision::Skip(reason) => {
+                            if verbose {
+                                info!("║ Trade not dispatched: {}", reason);
+                            }
+                            self.state.record_skip(&reason);
+                            trade.skip_reason = Some(reason);
+                        }
+                        Decision::Copy => {
+                            if let Some(webhook) = &self.webhook {
+                                webhook.send_trade(&trade);
+                            }
+                        }
+                    }
+                    if !verbose {
+                        info!("{}", self.output.trade_line(&trade, &self.quote_mints));
+                    }
+                    self.audit(&trade);
+                    self.state.digest().record(&trade, trade.skip_reason.is_none());
+                    self.state.target_pnl().record(&trade, self.mark_price(&trade));
+                    self.record_trade(&trade);
+                    self.state.push_trade(trade);
+                }
+                // 非成交交易合并为一行，Quiet时不输出
+                Ok(None) if self.output.verbosity == Verbosity::Normal => {
+                    info!("{} no trade in {}{}", self.output.wallet_prefix(&target_wallet), short_signature(&signature),
+                        dex_name.map(|dex| format!(" ({})", dex)).unwrap_or_default());
+                }
+                Ok(None) => {}
+                Err(e) if verbose => warn!("║ Failed to parse trade: {}", e),
+                Err(e) => warn!("{} failed to parse trade {}: {}", self.output.wallet_prefix(&target_wallet), short_signature(&signature), e),
+            }
+            
+            if !verbose {
+                return;
             }
             
             // Display transaction fee and analyze balance changes
-            if let Some(meta) = &tx_update.meta {
+            if let Some(meta) = &tx_info.meta {
                 let fee_sol = meta.fee as f64 / 1_000_000_000.0;
                 info!("║ Gas Fee: {} SOL", fee_sol);
                 
                 // Analyze balance changes
-                self.analyze_balance_changes(meta, &transaction.message);
+                let message = tx_info.transaction.as_ref().and_then(|tx| tx.message.clone());
+                self.analyze_balance_changes(meta, &message);
                 
                 // Display transaction logs (may contain useful information)
-                if meta.log_messages.len() > 0 {
+                if !meta.log_messages.is_empty() {
                     info!("║ ---- Transaction Logs ----");
                     for (i, log) in meta.log_messages.iter().enumerate() {
                         if log.contains("Swap") || log.contains("swap") || 
@@ -216,6 +1485,165 @@ impl GrpcMonitor {
         }
     }
 
+    // 流动性操作只记录计数，不作为成交分发
+    fn skip_liquidity(&self, event: &LiquidityEvent) {
+        let reason = SkipReason::Liquidity { action: event.action };
+        info!("{} {} liquidity {} on pool {} ({}), not copied",
+            self.output.wallet_prefix(&event.wallet), event.dex, event.action, event.pool, short_signature(&event.signature));
+        self.state.record_skip(&reason);
+    }
+
+    // 普通转账只在debug级别输出(详细模式下输出在交易框内)，按配置推送
+    fn skip_transfer(&self, transfer: &TransferEvent) {
+        let counterparty = if transfer.incoming { transfer.from } else { transfer.to };
+        let counterparty = counterparty.map(|key| key.to_string()).unwrap_or_else(|| "unknown".to_string());
+        let direction = if transfer.incoming { "from" } else { "to" };
+        if self.output.is_verbose() {
+            info!("║ Transfer of {} {} {} {}, not copied", transfer.amount, transfer.mint, direction, counterparty);
+        } else {
+            debug!("{} transfer of {} {} {} {} ({})", self.output.wallet_prefix(&transfer.wallet),
+                transfer.amount, transfer.mint, direction, counterparty, short_signature(&transfer.signature));
+        }
+        self.state.record_skip(&SkipReason::Transfer);
+        if let Some(webhook) = &self.webhook {
+            webhook.send_transfer(transfer);
+        }
+        self.flag_cluster_candidate(transfer);
+    }
+
+    fn flag_cluster_candidate(&self, transfer: &TransferEvent) {
+        let Some(config) = &self.cluster else { return };
+        let Some(candidate) = self.state.cluster().flag(config, transfer) else { return };
+        info!("Possible cluster wallet {} funded with {:.3} SOL by {} ({})", candidate.wallet,
+            candidate.funding_lamports as f64 / 1_000_000_000.0, candidate.funded_by, self.explorer.tx_url(&candidate.funding_signature));
+        if candidate.status == CandidateStatus::Watched {
+            info!("Cluster wallet {} added to the watch list", candidate.wallet);
+            self.state.request_resubscribe();
+        }
+        if let Some(webhook) = &self.webhook {
+            webhook.send_cluster_candidate(&candidate);
+        }
+    }
+
+    fn capture_parse_failure(&self, tx_update: &SubscribeUpdateTransaction, signature: &str, dex: &str, error: Option<String>) {
+        let Some(capture) = &self.parse_failures else { return };
+        let Some(tx_info) = &tx_update.transaction else { return };
+        let Some(tx) = &tx_info.transaction else { return };
+        if let Some(failure) = ParseFailure::new(signature, tx_update.slot, dex, error, tx, tx_info.meta.as_ref()) {
+            capture.capture(failure);
+        }
+    }
+
+    fn audit(&self, trade: &TradeDetails) {
+        if let Some(audit) = &self.audit {
+            audit.record(trade);
+        }
+    }
+
+    fn record_trade(&self, trade: &TradeDetails) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(trade);
+        }
+    }
+
+    /// 最近交易从接收到处理完成的延迟分布
+    pub fn latency_summary(&self) -> Option<LatencySummary> {
+        self.trade_latency.lock().unwrap_or_else(|e| e.into_inner()).summary()
+    }
+
+    fn log_latency(&self, trade: &TradeDetails, processed_elapsed: std::time::Duration) {
+        self.trade_latency.lock().unwrap_or_else(|e| e.into_inner()).record(processed_elapsed);
+        if !self.output.is_verbose() {
+            return;
+        }
+        
+        info!("║ Slot: {} | Receive → parse: {}µs | Receive → processed: {}µs",
+            trade.timeline.slot, trade.timeline.parse_micros, trade.timeline.processed_micros);
+        if let Some(summary) = self.latency_summary() {
+            info!("║ Processing latency over {} trades: p50 {}µs, p95 {}µs",
+                summary.samples, summary.p50.as_micros(), summary.p95.as_micros());
+        }
+    }
+
+    // 热路径只读缓存，缺失的价格交给后台刷新，供后续交易使用
+    // 稳定币计价按1美元估值，WSOL计价取预言机的SOL/USD价格
+    fn value_trade_in_usd(&self, trade: &mut TradeDetails) {
+        let Some(quote) = trade.quote_token(&self.quote_mints) else {
+            self.value_swap_in_usd(trade);
+            return;
+        };
+        let quote_amount = if trade.input_token == quote { trade.amount_in } else { trade.amount_out };
+        
+        let (quote_usd, quote_decimals) = match quote.to_string().as_str() {
+            USDC_MINT | USDT_MINT => (1.0, 6),
+            WSOL_MINT => {
+                let Some(oracle) = &self.price_oracle else { return };
+                match oracle.cached_price(WSOL_MINT) {
+                    Some(price) => (price.price_usd, 9),
+                    None => {
+                        oracle.refresh_in_background(WSOL_MINT);
+                        return;
+                    }
+                }
+            }
+            _ => return,
+        };
+        
+        let quote_unit = 10f64.powi(quote_decimals);
+        let value_usd = quote_amount as f64 / quote_unit * quote_usd;
+        trade.value_usd = Some(value_usd);
+        // price是计价代币最小单位/代币最小单位，换算成每个完整代币的美元价格；精度未知时不估算
+        trade.price_usd = trade.token_decimals
+            .map(|decimals| trade.price * 10f64.powi(decimals as i32) / quote_unit * quote_usd);
+        if self.output.is_verbose() {
+            info!("║ Value: ${:.2} (quote/USD {:.2})", value_usd, quote_usd);
+        }
+    }
+
+    // 代币互换没有计价一侧，按输出代币缓存的美元价格估值；price保持输入/输出最小单位之比
+    fn value_swap_in_usd(&self, trade: &mut TradeDetails) {
+        let (Some(oracle), Some(decimals)) = (&self.price_oracle, trade.token_decimals) else { return };
+        let output = trade.output_token.to_string();
+        let Some(price) = oracle.cached_price(&output) else {
+            oracle.refresh_in_background(&output);
+            return;
+        };
+        
+        let value_usd = trade.amount_out as f64 / 10f64.powi(decimals as i32) * price.price_usd;
+        trade.value_usd = Some(value_usd);
+        trade.price_usd = Some(price.price_usd);
+        if self.output.is_verbose() {
+            info!("║ Value: ${:.2} (token swap, output/USD {:.8})", value_usd, price.price_usd);
+        }
+    }
+
+    fn log_market_price(&self, trade: &TradeDetails) {
+        let oracle = match &self.price_oracle {
+            Some(oracle) => oracle,
+            None => return,
+        };
+        
+        let token_mint = if self.quote_mints.contains(&trade.input_token) {
+            trade.output_token
+        } else {
+            trade.input_token
+        };
+        let verbose = self.output.is_verbose();
+        if let Some(observed) = self.state.swap_prices().price(&token_mint).filter(|_| verbose) {
+            info!("║ Observed Price: {:.12} SOL ({} recent swaps)", observed.price_sol, observed.samples);
+        }
+        let token_mint = token_mint.to_string();
+        
+        match oracle.cached_price(&token_mint) {
+            Some(_) if !verbose => {}
+            Some(price) => match price.price_sol {
+                Some(price_sol) => info!("║ Market Price: ${:.8} ({:.12} SOL)", price.price_usd, price_sol),
+                None => info!("║ Market Price: ${:.8}", price.price_usd),
+            },
+            None => oracle.refresh_in_background(&token_mint),
+        }
+    }
+
     fn identify_dex(&self, transaction: &Transaction) -> Option<String> {
         if let Some(message) = &transaction.message {
             for account_key in &message.account_keys {
@@ -223,10 +1651,18 @@ impl GrpcMonitor {
                 
                 if key_str == RAYDIUM_V4 {
                     return Some("Raydium V4".to_string());
+                } else if key_str == PUMP_PROGRAM {
+                    return Some("Pump.fun".to_string());
                 } else if key_str == JUPITER_V6 {
                     return Some("Jupiter V6".to_string());
                 } else if key_str == ORCA_WHIRLPOOL {
                     return Some("Orca Whirlpool".to_string());
+                } else if key_str == MOONSHOT_PROGRAM {
+                    return Some("Moonshot".to_string());
+                } else if key_str == LIFINITY_V2_PROGRAM {
+                    return Some("Lifinity V2".to_string());
+                } else if key_str == PHOENIX_PROGRAM {
+                    return Some("Phoenix".to_string());
                 }
             }
         }
@@ -234,7 +1670,7 @@ impl GrpcMonitor {
     }
 
     fn analyze_balance_changes(&self, meta: &TransactionStatusMeta, message: &Option<Message>) {
-        if meta.pre_balances.len() > 0 && meta.post_balances.len() > 0 {
+        if !meta.pre_balances.is_empty() && !meta.post_balances.is_empty() {
             info!("║ ---- Balance Changes Analysis ----");
             
             let account_keys = message.as_ref()
@@ -253,10 +1689,10 @@ impl GrpcMonitor {
                     if change_sol.abs() > 0.0001 {
                         let account_str = if i < account_keys.len() {
                             let addr = &account_keys[i];
-                            if addr == self.target_wallet.to_string() {
-                                format!("Target Wallet")
-                            } else if addr == "So11111111111111111111111111111111111111112" {
-                                format!("SOL")
+                            if self.target_wallets.iter().any(|w| w.to_string() == *addr) {
+                                "Target Wallet".to_string()
+                            } else if addr == WSOL_MINT {
+                                "SOL".to_string()
                             } else {
                                 format!("{}...{}", &addr[..4], &addr[addr.len()-4..])
                             }
@@ -273,49 +1709,47 @@ impl GrpcMonitor {
                 }
             }
             
-            if meta.pre_token_balances.len() > 0 || meta.post_token_balances.len() > 0 {
+            if !meta.pre_token_balances.is_empty() || !meta.post_token_balances.is_empty() {
                 info!("║ ---- Token Balance Changes ----");
-                self.analyze_token_balance_changes(meta);
+                self.analyze_token_balance_changes(meta, &account_keys);
             }
         }
     }
 
-    fn analyze_token_balance_changes(&self, meta: &TransactionStatusMeta) {
-        let mut token_changes: HashMap<usize, (Option<u64>, Option<u64>, Option<String>)> = HashMap::new();
-        
-        for pre_balance in &meta.pre_token_balances {
-            let key = pre_balance.account_index as usize;
-            let amount = pre_balance.ui_token_amount.ui_amount_string.parse::<f64>().ok()
-                .map(|v| (v * 10f64.powi(pre_balance.ui_token_amount.decimals as i32)) as u64);
-            token_changes.entry(key).or_insert((None, None, None)).0 = amount;
-            token_changes.entry(key).or_insert((None, None, None)).2 = Some(pre_balance.mint.clone());
-        }
-        
-        for post_balance in &meta.post_token_balances {
-            let key = post_balance.account_index as usize;
-            let amount = post_balance.ui_token_amount.ui_amount_string.parse::<f64>().ok()
-                .map(|v| (v * 10f64.powi(post_balance.ui_token_amount.decimals as i32)) as u64);
-            token_changes.entry(key).or_insert((None, None, None)).1 = amount;
-            if token_changes.get(&key).unwrap().2.is_none() {
-                token_changes.entry(key).or_insert((None, None, None)).2 = Some(post_balance.mint.clone());
+    fn analyze_token_balance_changes(&self, meta: &TransactionStatusMeta, account_keys: &[String]) {
+        for entry in token_balance_changes(meta) {
+            let change = entry.delta();
+            if change == 0 || entry.mint.len() < 8 {
+                continue;
+            }
+            let mint_addr = &entry.mint;
+            let token_symbol = self.get_token_symbol(mint_addr);
+            let program = entry.program.unwrap_or(TokenProgram::Spl);
+            // 只有目标钱包持有的账户才需要推导ATA
+            let owner = self.target_wallets.iter().find(|w| w.to_string() == entry.owner);
+            let owner_label = match owner {
+                Some(owner) => self.target_account_label(owner, account_keys.get(entry.account_index as usize).map(|k| k.as_str()), mint_addr, program),
+                None => "",
+            };
+            
+            if change > 0 {
+                info!("║ Token received: +{} {} ({}...{}) [{}]{}", 
+                    change, token_symbol, &mint_addr[..4], &mint_addr[mint_addr.len()-4..], program, owner_label);
+            } else {
+                info!("║ Token sent: {} {} ({}...{}) [{}]{}", 
+                    change.unsigned_abs(), token_symbol, &mint_addr[..4], &mint_addr[mint_addr.len()-4..], program, owner_label);
             }
         }
+    }
+
+    // 标记目标钱包的代币账户，ATA按代币所属程序推导
+    fn target_account_label(&self, owner: &Pubkey, token_account: Option<&str>, mint: &str, program: TokenProgram) -> &'static str {
+        let expected_ata = Pubkey::from_str(mint).ok()
+            .map(|mint| program.associated_token_address(owner, &mint).to_string());
         
-        for (_account_index, (pre, post, mint)) in token_changes {
-            if let (Some(pre_amount), Some(post_amount), Some(mint_addr)) = (pre, post, mint) {
-                if pre_amount != post_amount {
-                    let change = post_amount as i64 - pre_amount as i64;
-                    let token_symbol = self.get_token_symbol(&mint_addr);
-                    
-                    if change > 0 {
-                        info!("║ Token received: +{} {} ({}...{})", 
-                            change, token_symbol, &mint_addr[..4], &mint_addr[mint_addr.len()-4..]);
-                    } else {
-                        info!("║ Token sent: {} {} ({}...{})", 
-                            change.abs(), token_symbol, &mint_addr[..4], &mint_addr[mint_addr.len()-4..]);
-                    }
-                }
-            }
+        match (token_account, expected_ata) {
+            (Some(account), Some(ata)) if account == ata => " Target Wallet ATA",
+            _ => " Target Wallet",
         }
     }
 
@@ -326,4 +1760,110 @@ impl GrpcMonitor {
             _ => "Unknown".to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+// 钱包拥有的全部代币账户(SPL和Token-2022)
+fn token_accounts_filter(wallet: &Pubkey) -> SubscribeRequestFilterAccounts {
+    SubscribeRequestFilterAccounts {
+        account: vec![],
+        owner: vec![TokenProgram::Spl.program_id().to_string(), TokenProgram::Token2022.program_id().to_string()],
+        filters: vec![SubscribeRequestFilterAccountsFilter {
+            filter: Some(AccountsFilter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                offset: TOKEN_ACCOUNT_OWNER_OFFSET,
+                data: Some(MemcmpData::Base58(wallet.to_string())),
+            })),
+        }],
+    }
+}
+
+// 目标钱包代币账户过滤器名称中的钱包
+fn target_tokens_wallet(filter: &str) -> Option<Pubkey> {
+    filter.strip_prefix(TARGET_WALLET_TOKENS_FILTER)?.strip_prefix(':')?.parse().ok()
+}
+
+// 交易涉及的目标钱包：静态账户中第一个目标钱包，发起交易的钱包总是在静态账户中
+fn transaction_target(target_wallets: &[Pubkey], tx: &Transaction) -> Pubkey {
+    let keys = tx.message.as_ref().map(|m| m.account_keys.as_slice()).unwrap_or_default();
+    target_wallets.iter()
+        .find(|wallet| keys.iter().any(|key| key.as_slice() == wallet.as_ref()))
+        .copied()
+        .unwrap_or(target_wallets[0])
+}
+
+// 更新所携带的slot
+fn update_slot(msg: &SubscribeUpdate) -> Option<u64> {
+    use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+    
+    match msg.update_oneof.as_ref()? {
+        UpdateOneof::Slot(slot) => Some(slot.slot),
+        UpdateOneof::Transaction(tx) => Some(tx.slot),
+        UpdateOneof::Account(account) => Some(account.slot),
+        _ => None,
+    }
+}
+
+fn is_failed(update: &SubscribeUpdateTransaction) -> bool {
+    update.transaction.as_ref()
+        .and_then(|info| info.meta.as_ref())
+        .is_some_and(|meta| meta.err.is_some())
+}
+
+fn is_target_transaction(msg: &SubscribeUpdate) -> bool {
+    use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+    
+    matches!(msg.update_oneof, Some(UpdateOneof::Transaction(_)))
+        && msg.filters.iter().any(|f| f.starts_with(TARGET_TX_FILTER))
+}
+
+// 在工作线程上执行；预过滤没有已知成交指令时返回None，没有解析器匹配时退回到余额变化推断
+fn parse_target_transaction(
+    parser: &TransactionParser,
+    target_wallets: &[Pubkey],
+    prefilter: bool,
+    msg: &SubscribeUpdate,
+) -> Option<ParseResult> {
+    use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+    
+    let Some(UpdateOneof::Transaction(update)) = &msg.update_oneof else { return None };
+    if is_failed(update) {
+        return None;
+    }
+    let info = update.transaction.as_ref()?;
+    let tx = info.transaction.as_ref()?;
+    if prefilter && !parser.has_trade_instruction(tx, info.meta.as_ref()) {
+        return None;
+    }
+    let signature = bs58::encode(&info.signature).into_string();
+    Some(parser.classify(&signature, tx, info.meta.as_ref(), &transaction_target(target_wallets, tx)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_requires_wallets_and_routes_transactions_to_their_target() {
+        assert!(GrpcMonitor::builder().endpoint("http://localhost:10000").build().is_err());
+        assert!(GrpcMonitor::builder().target_wallet(Pubkey::new_unique()).build().is_err());
+
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let monitor = GrpcMonitor::builder()
+            .endpoint("http://localhost:10000")
+            .target_wallets([first, second])
+            .build()
+            .unwrap();
+        let request = monitor.subscribe_request();
+        assert_eq!(request.accounts["wallet"].account, vec![first.to_string(), second.to_string()]);
+        assert_eq!(target_tokens_wallet(&format!("{}:{}", TARGET_WALLET_TOKENS_FILTER, second)), Some(second));
+        assert!(request.accounts.contains_key(&format!("{}:{}", TARGET_WALLET_TOKENS_FILTER, first)));
+
+        let tx = Transaction {
+            message: Some(Message {
+                account_keys: vec![second.to_bytes().to_vec(), Pubkey::new_unique().to_bytes().to_vec()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(transaction_target(&monitor.target_wallets, &tx), second);
+    }
+}
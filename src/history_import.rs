@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use crate::config::Config;
+use wallet_copier::gap_recovery;
+use wallet_copier::parser::TransactionParser;
+use wallet_copier::recorder::{RecordFilter, TradeRecorder};
+use wallet_copier::rpc_budget::RpcBudget;
+use wallet_copier::types::{QuoteMints, TradeKind};
+
+// 单次导入最多拉取的签名数
+const DEFAULT_MAX_SIGNATURES: usize = 5_000;
+
+// 导入参数: --days <N> --max <签名数>
+pub struct ImportOptions {
+    pub days: u64,
+    pub max_signatures: usize,
+}
+
+impl ImportOptions {
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut options = ImportOptions { days: 7, max_signatures: DEFAULT_MAX_SIGNATURES };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().with_context(|| format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--days" => options.days = value.parse()?,
+                "--max" => options.max_signatures = value.parse()?,
+                other => anyhow::bail!("Unknown import-history option {}", other),
+            }
+        }
+        Ok(options)
+    }
+}
+
+// 通过RPC拉取钱包过去N天的交易，解析后写入交易记录，供analyze在跟单前评估钱包
+pub async fn import_history(config: &Config, wallet: &Pubkey, options: &ImportOptions) -> Result<()> {
+    let recorder_config = config.recorder.clone()
+        .ok_or_else(|| anyhow::anyhow!("config.json中未配置recorder"))?;
+    let recorder = TradeRecorder::new(recorder_config)?;
+    let since = chrono::Utc::now().timestamp() - (options.days * 86_400) as i64;
+
+    // 已导入过的交易不重复写入
+    let existing: HashSet<String> = recorder
+        .read_records(&RecordFilter { since: Some(since), wallet: Some(*wallet), ..Default::default() })?
+        .into_iter()
+        .map(|trade| trade.signature)
+        .collect();
+
+    let rpc = RpcClient::new(config.rpc_url.clone());
+    let budget = RpcBudget::new(config.rpc_budget.clone());
+    println!("Fetching transactions of {} from the last {} days...", wallet, options.days);
+    let transactions = gap_recovery::fetch_transactions_since_time(&rpc, &budget, wallet, since, options.max_signatures).await?;
+
+    let parser = TransactionParser::new().with_quote_mints(QuoteMints::from_config(&config.quote_mints)?);
+    let mut trades = Vec::new();
+    let (mut skipped, mut failed, mut not_trades) = (0, 0, 0);
+    for tx in &transactions {
+        if existing.contains(&tx.signature) {
+            skipped += 1;
+            continue;
+        }
+        match parser.classify(&tx.signature, &tx.transaction, tx.meta.as_ref(), wallet) {
+            Ok(Some(TradeKind::Swap(mut trade))) => {
+                trade.timeline.slot = tx.slot;
+                if let Some(block_time) = tx.block_time {
+                    trade.timestamp = block_time;
+                }
+                trades.push(trade);
+            }
+            Ok(Some(TradeKind::Liquidity(_) | TradeKind::Transfer(_))) => not_trades += 1,
+            Ok(None) => {}
+            Err(_) => failed += 1,
+        }
+    }
+
+    recorder.write_batch(&trades)?;
+    println!("Imported {} trades from {} transactions ({} already recorded, {} transfers or liquidity operations, {} failed to parse)",
+        trades.len(), transactions.len(), skipped, not_trades, failed);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// 保留的最近样本数量
+const MAX_SAMPLES: usize = 1000;
+
+/// 最近成交从接收到处理完成的延迟分布
+#[derive(Debug, Clone, Copy)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p95: Duration,
+}
+
+// 滚动窗口延迟统计
+pub struct LatencyStats {
+    samples: VecDeque<Duration>,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyStats {
+    pub fn new() -> Self {
+        LatencyStats {
+            samples: VecDeque::with_capacity(MAX_SAMPLES),
+        }
+    }
+
+    pub fn record(&mut self, sample: Duration) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn summary(&self) -> Option<LatencySummary> {
+        Some(LatencySummary {
+            samples: self.samples.len(),
+            p50: self.percentile(0.5)?,
+            p95: self.percentile(0.95)?,
+        })
+    }
+
+    // 百分位数，p取值0.0~1.0，无样本时返回None
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((sorted.len() - 1) as f64 * p.clamp(0.0, 1.0)).round() as usize;
+        Some(sorted[rank])
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use std::collections::{HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+// 保留的已处理签名数量
+const LEDGER_CAPACITY: usize = 10_000;
+
+struct LedgerInner {
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+    file: Option<File>,
+}
+
+// 已处理交易签名的账本，防止重连重放或重复推送导致同一笔交易被分发两次
+// 配置了文件时每条签名追加写入，重启后仍然有效
+pub struct SignatureLedger {
+    inner: Mutex<LedgerInner>,
+}
+
+impl SignatureLedger {
+    pub fn in_memory() -> Self {
+        SignatureLedger {
+            inner: Mutex::new(LedgerInner {
+                seen: HashSet::new(),
+                order: VecDeque::with_capacity(LEDGER_CAPACITY),
+                file: None,
+            }),
+        }
+    }
+
+    // 读取已有记录；超出容量时只保留最新的部分并重写文件
+    pub fn open(path: &str) -> Result<Self> {
+        let existing = std::fs::read_to_string(path).unwrap_or_default();
+        let lines: Vec<&str> = existing.lines().filter(|l| !l.is_empty()).collect();
+        let kept = &lines[lines.len().saturating_sub(LEDGER_CAPACITY)..];
+        if kept.len() < lines.len() {
+            let mut content = kept.join("\n");
+            content.push('\n');
+            std::fs::write(path, content).with_context(|| format!("Unable to compact ledger {}", path))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Unable to open ledger {}", path))?;
+        let ledger = SignatureLedger::in_memory();
+        {
+            let mut inner = ledger.inner.lock().unwrap_or_else(|e| e.into_inner());
+            for signature in kept {
+                inner.remember(signature.to_string());
+            }
+            inner.file = Some(file);
+        }
+        Ok(ledger)
+    }
+
+    // 已登记的签名，从旧到新
+    pub fn signatures(&self) -> Vec<String> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.order.iter().cloned().collect()
+    }
+
+    // 首次见到该签名时登记并返回true，已处理过则返回false
+    pub fn claim(&self, signature: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.seen.contains(signature) {
+            return false;
+        }
+        inner.remember(signature.to_string());
+        if let Some(file) = inner.file.as_mut() {
+            if let Err(e) = writeln!(file, "{}", signature) {
+                tracing::warn!("Failed to persist signature to ledger: {}", e);
+            }
+        }
+        true
+    }
+}
+
+impl LedgerInner {
+    fn remember(&mut self, signature: String) {
+        if self.order.len() == LEDGER_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(signature.clone());
+        self.order.push_back(signature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_persist_across_reopen() {
+        let path = std::env::temp_dir().join(format!("ledger_test_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let ledger = SignatureLedger::open(path).unwrap();
+        assert!(ledger.claim("sig1"));
+        assert!(!ledger.claim("sig1"));
+        drop(ledger);
+
+        let reopened = SignatureLedger::open(path).unwrap();
+        assert!(!reopened.claim("sig1"));
+        assert!(reopened.claim("sig2"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+//! Solana钱包交易监控与解析库。
+//!
+//! 通过Yellowstone gRPC订阅目标钱包的交易，解析出DEX成交([`TradeDetails`])，
+//! 并经由策略、webhook、记录器等分发。命令行程序只是在此之上读取`config.json`
+//! 组装各组件，嵌入其他项目时可直接使用这些类型，不依赖配置文件:
+//!
+//! ```no_run
+//! use std::str::FromStr;
+//! use solana_sdk::pubkey::Pubkey;
+//! use wallet_copier::GrpcMonitor;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let wallet = Pubkey::from_str("11111111111111111111111111111111")?;
+//! let monitor = GrpcMonitor::builder()
+//!     .endpoint("https://grpc.example.com")
+//!     .target_wallet(wallet)
+//!     .build()?;
+//! monitor.start_monitoring().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 只需解析交易时使用[`TransactionParser`]。自定义是否分发成交时实现[`Strategy`]，
+//! 通过[`GrpcMonitorBuilder::strategy`]传入。
+
+pub mod analytics;
+pub mod annotations;
+pub mod api;
+pub mod audit;
+pub mod bot_state;
+pub mod cluster;
+pub mod digest;
+pub mod display;
+pub(crate) mod error;
+pub mod explorer;
+pub mod gap_recovery;
+pub mod parse_failures;
+pub mod parser;
+pub mod price_oracle;
+pub mod profiles;
+pub mod recorder;
+pub mod rpc_budget;
+pub mod snapshot;
+pub mod types;
+pub mod grpc_monitor;
+pub(crate) mod latency;
+pub mod logging;
+pub mod ledger;
+pub mod token_program;
+pub mod strategy;
+pub(crate) mod swap_prices;
+pub(crate) mod stream_compat;
+pub mod stream_health;
+pub mod wallet_balances;
+pub mod watchlist;
+pub mod webhook;
+
+pub use bot_state::BotState;
+pub use grpc_monitor::{GrpcMonitor, GrpcMonitorBuilder};
+pub use latency::LatencySummary;
+pub use parser::TransactionParser;
+pub use strategy::{Decision, MirrorStrategy, Strategy};
+pub use types::{DexType, QuoteMints, SkipReason, TradeDetails, TradeDirection, TradeKind};
@@ -0,0 +1,50 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::sync::{Mutex, OnceLock};
+use tracing::info;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+const DEFAULT_FILTER: &str = "info";
+
+static CONTROL: OnceLock<LogControl> = OnceLock::new();
+
+// 运行中可替换的日志过滤器
+struct LogControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    current: Mutex<String>,
+}
+
+// 初始化日志；过滤器格式同RUST_LOG(如 "info,wallet_copier::parser=debug")，环境变量优先于配置。
+// 设置了log_file时日志写入文件且不带颜色，避免破坏TUI界面
+pub fn init(configured: Option<&str>, log_file: Option<File>) -> Result<()> {
+    let spec = std::env::var("RUST_LOG").ok()
+        .filter(|spec| !spec.is_empty())
+        .or_else(|| configured.map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string());
+    let (filter, handle) = reload::Layer::new(parse(&spec)?);
+    let registry = tracing_subscriber::registry().with(filter);
+    match log_file {
+        Some(file) => registry.with(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))).init(),
+        None => registry.with(fmt::layer()).init(),
+    }
+    let _ = CONTROL.set(LogControl { handle, current: Mutex::new(spec) });
+    Ok(())
+}
+
+pub fn set_filter(spec: &str) -> Result<()> {
+    let control = CONTROL.get().context("Logging is not initialized")?;
+    control.handle.reload(parse(spec)?).context("Unable to replace log filter")?;
+    *control.current.lock().unwrap_or_else(|e| e.into_inner()) = spec.to_string();
+    info!("Log filter changed to {}", spec);
+    Ok(())
+}
+
+pub fn current_filter() -> Option<String> {
+    let control = CONTROL.get()?;
+    Some(control.current.lock().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+fn parse(spec: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(spec).with_context(|| format!("Invalid log filter {:?}", spec))
+}
@@ -1,39 +1,284 @@
-// https://solana-rpc.publicnode.com/f884f7c2cfa0e7ecbf30e7da70ec1da91bda3c9d04058269397a5591e7fd013e";
-// CuwxHwz42cNivJqWGBk6HcVvfGq47868Mo6zi4u6z9vC
-
-mod parser;
-mod types;
-mod grpc_monitor;
+mod config;
+mod history_import;
+mod tui;
+mod status;
 
 use anyhow::Result;
-use grpc_monitor::GrpcMonitor;
+use config::Config;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
+use std::sync::Arc;
 use tracing::{info, error};
+use wallet_copier::{analytics, annotations, api, audit, digest, ledger, logging, profiles, recorder, rpc_budget, snapshot, strategy};
+use wallet_copier::display::{ConsoleOutput, Verbosity};
+use wallet_copier::{BotState, GrpcMonitor};
+use wallet_copier::parse_failures::ParseFailureCapture;
+use wallet_copier::price_oracle::PriceOracle;
+use wallet_copier::types::QuoteMints;
+use wallet_copier::wallet_balances::WalletBalances;
+use wallet_copier::webhook::WebhookSink;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 初始化日志系统
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    let tui_mode = std::env::args().any(|arg| arg == "--tui");
+    
+    let config = Config::load()?;
+    
+    // 初始化日志系统；TUI模式下日志写入文件，避免破坏界面
+    let log_file = if tui_mode { Some(std::fs::File::create("wallet_copier.log")?) } else { None };
+    logging::init(config.log_filter.as_deref(), log_file)?;
+    
+    // 子命令: history 读取交易记录文件
+    if std::env::args().nth(1).as_deref() == Some("history") {
+        let recorder_config = config.recorder.clone()
+            .ok_or_else(|| anyhow::anyhow!("config.json中未配置recorder"))?;
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        let recorder = recorder::TradeRecorder::new(recorder_config)?;
+        return recorder::print_history(&recorder, &recorder::RecordFilter::from_args(&args)?);
+    }
+    
+    // 子命令: analyze <钱包> 根据交易记录输出钱包行为报告
+    if std::env::args().nth(1).as_deref() == Some("analyze") {
+        let recorder_config = config.recorder.clone()
+            .ok_or_else(|| anyhow::anyhow!("config.json中未配置recorder"))?;
+        let wallet = std::env::args().nth(2)
+            .ok_or_else(|| anyhow::anyhow!("用法: analyze <wallet> [--since <unix秒>] [--until <unix秒>]"))?;
+        let args: Vec<String> = std::env::args().skip(3).collect();
+        let recorder = recorder::TradeRecorder::new(recorder_config)?;
+        return analytics::print_report(&recorder, &Pubkey::from_str(&wallet)?, &recorder::RecordFilter::from_args(&args)?);
+    }
+    
+    // 子命令: annotate <签名> 给交易添加标签和备注
+    if std::env::args().nth(1).as_deref() == Some("annotate") {
+        let recorder_config = config.recorder.clone()
+            .ok_or_else(|| anyhow::anyhow!("config.json中未配置recorder"))?;
+        let usage = "用法: annotate <signature> [--tag <标签>]... [--note <备注>]";
+        let signature = std::env::args().nth(2).ok_or_else(|| anyhow::anyhow!(usage))?;
+        let (mut tags, mut note) = (Vec::new(), None);
+        let mut args = std::env::args().skip(3);
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| anyhow::anyhow!(usage))?;
+            match flag.as_str() {
+                "--tag" => tags.push(value),
+                "--note" => note = Some(value),
+                _ => anyhow::bail!(usage),
+            }
+        }
+        let annotation = recorder::TradeRecorder::new(recorder_config)?.annotations()?.annotate(&signature, &tags, note)?;
+        println!("{}: tags [{}]{}", signature, annotation.tags.join(", "),
+            annotation.note.map(|note| format!(" note: {}", note)).unwrap_or_default());
+        return Ok(());
+    }
+    
+    // 子命令: import-history <钱包> 通过RPC导入钱包的历史交易，供analyze评估
+    if std::env::args().nth(1).as_deref() == Some("import-history") {
+        let wallet = std::env::args().nth(2)
+            .ok_or_else(|| anyhow::anyhow!("用法: import-history <wallet> [--days <N>] [--max <签名数>]"))?;
+        let args: Vec<String> = std::env::args().skip(3).collect();
+        let options = history_import::ImportOptions::from_args(&args)?;
+        return history_import::import_history(&config, &Pubkey::from_str(&wallet)?, &options).await;
+    }
+    
+    // 子命令: status 打印跟单钱包余额和持仓
+    if std::env::args().nth(1).as_deref() == Some("status") {
+        let price_oracle = PriceOracle::with_default_providers();
+        return status::print_status(&config, &price_oracle).await;
+    }
+    
+    // 子命令: log-filter [<过滤器>] 通过REST API查看或修改运行中程序的日志过滤器
+    if std::env::args().nth(1).as_deref() == Some("log-filter") {
+        let api_config = config.api.clone()
+            .ok_or_else(|| anyhow::anyhow!("config.json中未配置api"))?;
+        let filter = api::request_log_filter(&api_config, std::env::args().nth(2)).await?;
+        println!("Log filter: {}", filter);
+        return Ok(());
+    }
+    
+    // 子命令: audit-verify 校验审计日志的HMAC链
+    if std::env::args().nth(1).as_deref() == Some("audit-verify") {
+        let audit_config = config.audit.clone()
+            .ok_or_else(|| anyhow::anyhow!("config.json中未配置audit"))?;
+        return audit::print_verification(&audit_config);
+    }
+    
+    // 子命令: profiles list | profiles show [<钱包>] 查看内置profile和目标钱包合并后的交易参数
+    if std::env::args().nth(1).as_deref() == Some("profiles") {
+        match std::env::args().nth(2).as_deref() {
+            Some("list") => {
+                for name in profiles::BUILTIN {
+                    let preset = profiles::builtin(name).expect("builtin profile");
+                    profiles::print_settings(name, &preset.apply(&config.trading_settings));
+                }
+            }
+            Some("show") => {
+                let wallets = match std::env::args().nth(3) {
+                    Some(wallet) => vec![wallet],
+                    None => config.target_wallets.clone(),
+                };
+                for wallet in wallets {
+                    let (profile, settings) = config.settings_for(&wallet)?;
+                    profiles::print_settings(&format!("{} ({})", wallet, profile), &settings);
+                }
+            }
+            _ => anyhow::bail!("用法: profiles list | profiles show [<wallet>]"),
+        }
+        return Ok(());
+    }
     
     info!("启动Solana钱包监控程序 (gRPC模式)");
     
     // 配置信息
-    let grpc_endpoint = "https://solana-yellowstone-grpc.publicnode.com:443"; // 需要替换为实际的gRPC端点
-    let auth_token = Some("your-auth-token".to_string()); // 如果需要认证令牌
-    let wallet_address = "CuwxHwz42cNivJqWGBk6HcVvfGq47868Mo6zi4u6z9vC";
-    let wallet_pubkey = Pubkey::from_str(wallet_address)?;
+    if config.target_wallets.is_empty() {
+        anyhow::bail!("config.json中未配置target_wallets");
+    }
+    let target_wallets = config.target_wallets.iter()
+        .map(|w| Pubkey::from_str(w))
+        .collect::<Result<Vec<_>, _>>()?;
+    for wallet in &config.target_wallets {
+        let (profile, settings) = config.settings_for(wallet)?;
+        info!("目标钱包 {} 使用profile {}: 仓位 {} SOL, 滑点 {}", wallet, profile, settings.max_position_size, settings.slippage_tolerance);
+    }
+    
+    // 价格预言机
+    let price_oracle = Arc::new(PriceOracle::with_default_providers());
     
     // 创建gRPC监控器
-    let monitor = GrpcMonitor::new(grpc_endpoint.to_string(), auth_token, wallet_pubkey);
+    // 跟单钱包和目标钱包的余额先由RPC初始化，之后由gRPC账户更新维护
+    let copy_wallet = config.copy_wallet_pubkey()?;
+    let rpc_client = Arc::new(RpcClient::new(config.rpc_url.clone()));
+    let startup_budget = rpc_budget::RpcBudget::new(config.rpc_budget.clone());
+    let copy_wallet_balances = WalletBalances::fetch(&rpc_client, &startup_budget, &copy_wallet).await?;
     
-    // 启动监控
-    match monitor.start_monitoring().await {
-        Ok(_) => info!("gRPC监控正常结束"),
-        Err(e) => error!("gRPC监控出错: {}", e),
+    let quote_mints = QuoteMints::from_config(&config.quote_mints)?;
+    // 动量过滤需要读取监控器的状态，先创建状态再交给监控器
+    let state = Arc::new(BotState::new());
+    let mut strategy = strategy::build(config.strategy.as_deref(), quote_mints.clone())?;
+    if let Some(momentum) = config.momentum.clone() {
+        info!("动量过滤: 最近{}笔成交涨幅超过{}%时不跟买", momentum.window, momentum.max_rise_pct);
+        strategy = Arc::new(strategy::MomentumFilter::new(strategy, momentum, Arc::clone(&state), quote_mints.clone()));
+    }
+    let mut builder = GrpcMonitor::builder()
+        .endpoint(config.grpc_endpoint.clone())
+        .auth_token(config.grpc_auth_token.clone())
+        .target_wallets(target_wallets.iter().copied())
+        .state(state)
+        .price_oracle(price_oracle)
+        .copy_wallet(copy_wallet, copy_wallet_balances)
+        .transaction_filter(config.transaction_filter.clone())
+        .dex_switches(config.dex_switches.clone())
+        .channel(config.grpc_channel.clone())
+        .rpc(Arc::clone(&rpc_client))
+        .stream_health(config.stream_health.clone())
+        .quote_mints(quote_mints)
+        .strategy(strategy)
+        .rpc_budget(config.rpc_budget.clone())
+        .pipeline(config.pipeline.clone())
+        .explorer(config.explorer.clone())
+        .output(ConsoleOutput {
+            verbosity: Verbosity::from_args(&std::env::args().collect::<Vec<_>>()),
+            colors: !tui_mode,
+        });
+    for wallet in &target_wallets {
+        builder = builder.target_balances(*wallet, WalletBalances::fetch(&rpc_client, &startup_budget, wallet).await?);
+    }
+    if !config.watch_wallets.is_empty() {
+        let watch_wallets = config.watch_wallets.iter()
+            .map(|w| Pubkey::from_str(w))
+            .collect::<Result<Vec<_>, _>>()?;
+        info!("观察名单: {} 个钱包(只记录不跟单)", watch_wallets.len());
+        builder = builder.watch_wallets(watch_wallets);
+    }
+    if let Some(recorder_config) = config.recorder.clone() {
+        info!("交易记录写入目录: {}", recorder_config.dir);
+        builder = builder.recorder(recorder::TradeRecorder::new(recorder_config)?.spawn());
+    }
+    if let Some(ledger_file) = &config.ledger_file {
+        builder = builder.ledger(ledger::SignatureLedger::open(ledger_file)?);
+    }
+    if let Some(pool_config) = config.new_pools.clone() {
+        info!("新池子检测: {} 个程序{}", pool_config.programs.len(), if pool_config.exclusive { " (仅检测新池子)" } else { "" });
+        builder = builder.new_pool_detection(pool_config);
+    }
+    if let Some(capture_config) = config.parse_failures.clone() {
+        info!("解析失败的交易保存到: {}", capture_config.dir);
+        builder = builder.parse_failure_capture(ParseFailureCapture::new(capture_config)?);
+    }
+    if let Some(audit_config) = &config.audit {
+        info!("分发决策审计日志: {}", audit_config.file);
+        builder = builder.audit_log(audit::AuditLog::open(audit_config, config.hash()?)?);
+    }
+    if let Some(cluster_config) = config.cluster.clone() {
+        info!("关联钱包检测: 向新地址转出至少 {} SOL{}", cluster_config.min_transfer_sol, if cluster_config.auto_watch { " (自动加入观察名单)" } else { "" });
+        builder = builder.cluster_detection(cluster_config);
+    }
+    if let Some(gap_config) = config.gap_recovery.clone() {
+        builder = builder.gap_recovery(gap_config);
+    }
+    let webhook = config.webhook.clone().map(|webhook_config| {
+        info!("交易事件将推送到webhook: {}", webhook_config.url);
+        Arc::new(WebhookSink::new(webhook_config, config.explorer.clone()))
+    });
+    if let Some(webhook) = &webhook {
+        builder = builder.notifier(Arc::clone(webhook));
     }
+    let monitor = builder.build()?;
+    if let Some(digest_config) = config.digest.clone() {
+        info!("每{}分钟输出一次交易摘要", digest_config.interval_minutes);
+        digest::spawn(digest_config, monitor.state(), webhook);
+    }
+    if let Some(snapshot_config) = &config.snapshot {
+        snapshot::restore_from_file(snapshot_config, &monitor.state(), &monitor.ledger())?;
+        snapshot::spawn(snapshot_config.clone(), monitor.state(), monitor.ledger());
+    }
+    
+    // REST API
+    if let Some(api_config) = config.api.clone() {
+        let state = monitor.state();
+        let explorer = config.explorer.clone();
+        let annotations = match &config.recorder {
+            Some(recorder_config) => Some(Arc::new(annotations::AnnotationStore::load(std::path::Path::new(&recorder_config.dir))?)),
+            None => None,
+        };
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(api_config, state, annotations, explorer).await {
+                error!("REST API退出: {:?}", e);
+            }
+        });
+    }
+    
+    // TUI模式: 监控在后台运行，界面退出即结束程序
+    if tui_mode {
+        let monitor = Arc::new(monitor);
+        let background = Arc::clone(&monitor);
+        tokio::spawn(async move {
+            if let Err(e) = background.start_monitoring().await {
+                error!("gRPC监控出错: {}", e);
+            }
+        });
+        let result = tui::run(Arc::clone(&monitor)).await;
+        save_snapshot(&config, &monitor).await;
+        return result;
+    }
+    
+    // 启动监控，Ctrl+C时保存快照后退出
+    tokio::select! {
+        result = monitor.start_monitoring() => match result {
+            Ok(_) => info!("gRPC监控正常结束"),
+            Err(e) => error!("gRPC监控出错: {}", e),
+        },
+        _ = tokio::signal::ctrl_c() => info!("收到退出信号"),
+    }
+    save_snapshot(&config, &monitor).await;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn save_snapshot(config: &Config, monitor: &GrpcMonitor) {
+    let Some(snapshot_config) = &config.snapshot else { return };
+    let runtime = snapshot::capture(&monitor.state(), &monitor.ledger());
+    match snapshot::save(&snapshot_config.file, &runtime).await {
+        Ok(()) => info!("运行时状态已保存到 {}", snapshot_config.file),
+        Err(e) => error!("保存运行时状态失败: {:?}", e),
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{info, warn};
+use yellowstone_grpc_proto::prelude::{Transaction, TransactionStatusMeta};
+use crate::parser::resolve_account_keys;
+
+// 已知DEX程序的交易解析失败时保存原始数据，供补充解析支持
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseFailureConfig {
+    #[serde(default = "default_dir")]
+    pub dir: String,
+    // 最多保存的文件数，避免持续失败时写满磁盘
+    #[serde(default = "default_max_captures")]
+    pub max_captures: usize,
+}
+
+fn default_dir() -> String {
+    "parse_failures".to_string()
+}
+
+fn default_max_captures() -> usize {
+    1000
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapturedInstruction {
+    pub program: String,
+    // CPI内部指令所属的顶层指令序号，顶层指令为None
+    pub outer_index: Option<u32>,
+    pub accounts: Vec<u8>,
+    pub data_hex: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParseFailure {
+    pub signature: String,
+    pub slot: u64,
+    pub dex: String,
+    // 解析返回错误时的错误信息，返回None(未识别出成交)时为空
+    pub error: Option<String>,
+    pub account_keys: Vec<String>,
+    pub instructions: Vec<CapturedInstruction>,
+    pub log_messages: Vec<String>,
+}
+
+impl ParseFailure {
+    pub fn new(
+        signature: &str,
+        slot: u64,
+        dex: &str,
+        error: Option<String>,
+        tx: &Transaction,
+        meta: Option<&TransactionStatusMeta>,
+    ) -> Option<Self> {
+        let message = tx.message.as_ref()?;
+        let account_keys = resolve_account_keys(message, meta).ok()?;
+        let program = |index: u32| account_keys.get(index as usize)
+            .map(|key| key.to_string())
+            .unwrap_or_else(|| format!("#{}", index));
+
+        let outer = message.instructions.iter()
+            .map(|ix| CapturedInstruction {
+                program: program(ix.program_id_index),
+                outer_index: None,
+                accounts: ix.accounts.clone(),
+                data_hex: hex::encode(&ix.data),
+            });
+        let inner = meta.into_iter()
+            .flat_map(|m| m.inner_instructions.iter())
+            .flat_map(|inner| inner.instructions.iter().map(move |ix| CapturedInstruction {
+                program: program(ix.program_id_index),
+                outer_index: Some(inner.index),
+                accounts: ix.accounts.clone(),
+                data_hex: hex::encode(&ix.data),
+            }));
+        let instructions = outer.chain(inner).collect();
+
+        Some(ParseFailure {
+            signature: signature.to_string(),
+            slot,
+            dex: dex.to_string(),
+            error,
+            account_keys: account_keys.iter().map(|key| key.to_string()).collect(),
+            instructions,
+            log_messages: meta.map(|m| m.log_messages.clone()).unwrap_or_default(),
+        })
+    }
+}
+
+// 每笔失败写成 <dir>/<签名>.json，写入在后台完成
+pub struct ParseFailureCapture {
+    config: ParseFailureConfig,
+    captured: AtomicUsize,
+}
+
+impl ParseFailureCapture {
+    pub fn new(config: ParseFailureConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        Ok(ParseFailureCapture { config, captured: AtomicUsize::new(0) })
+    }
+
+    pub fn capture(&self, failure: ParseFailure) {
+        let count = self.captured.fetch_add(1, Ordering::Relaxed);
+        if count >= self.config.max_captures {
+            if count == self.config.max_captures {
+                warn!("Parse failure capture limit ({}) reached, further failures are not saved", self.config.max_captures);
+            }
+            return;
+        }
+        let path = PathBuf::from(&self.config.dir).join(format!("{}.json", failure.signature));
+        tokio::spawn(async move {
+            let result = match serde_json::to_vec_pretty(&failure) {
+                Ok(body) => tokio::fs::write(&path, body).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(()) => info!("Saved {} parse failure to {}", failure.dex, path.display()),
+                Err(e) => warn!("Failed to save parse failure {}: {:?}", failure.signature, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::prelude::{CompiledInstruction, InnerInstruction, InnerInstructions, Message};
+
+    #[test]
+    fn captures_raw_instructions_and_logs() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let tx = Transaction {
+            message: Some(Message {
+                account_keys: keys.iter().map(|key| key.to_bytes().to_vec()).collect(),
+                instructions: vec![CompiledInstruction { program_id_index: 2, accounts: vec![0, 1], data: vec![9, 0xab] }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            inner_instructions: vec![InnerInstructions {
+                index: 0,
+                instructions: vec![InnerInstruction { program_id_index: 1, accounts: vec![0], data: vec![1], stack_height: None }],
+            }],
+            log_messages: vec!["Program log: swap".to_string()],
+            ..Default::default()
+        };
+
+        let failure = ParseFailure::new("sig", 7, "Raydium", None, &tx, Some(&meta)).unwrap();
+        assert_eq!(failure.instructions.len(), 2);
+        assert_eq!(failure.instructions[0].program, keys[2].to_string());
+        assert_eq!(failure.instructions[0].data_hex, "09ab");
+        assert_eq!(failure.instructions[1].outer_index, Some(0));
+        assert_eq!(failure.log_messages, vec!["Program log: swap".to_string()]);
+    }
+}
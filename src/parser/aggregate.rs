@@ -0,0 +1,131 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use crate::types::{QuoteMints, TradeDetails};
+
+// 把同一笔交易里的多条成交(如拆单到多个池子)合并成一笔净成交
+// 从钱包角度统计每个mint的净流入/流出，净流出最多的为输入，净流入最多的为输出；全部抵消时返回None
+pub fn aggregate_legs(legs: Vec<TradeDetails>, quotes: &QuoteMints) -> Option<TradeDetails> {
+    if legs.len() <= 1 {
+        return legs.into_iter().next();
+    }
+
+    let mut net: HashMap<Pubkey, i128> = HashMap::new();
+    let mut decimals: HashMap<Pubkey, u8> = HashMap::new();
+    for leg in &legs {
+        *net.entry(leg.input_token).or_default() -= leg.amount_in as i128;
+        *net.entry(leg.output_token).or_default() += leg.amount_out as i128;
+        if let Some(d) = leg.token_decimals {
+            let token = if quotes.contains(&leg.input_token) { leg.output_token } else { leg.input_token };
+            decimals.insert(token, d);
+        }
+    }
+
+    let (input_token, spent) = net.iter()
+        .filter(|(_, amount)| **amount < 0)
+        .min_by_key(|(_, amount)| **amount)
+        .map(|(mint, amount)| (*mint, amount.unsigned_abs() as u64))?;
+    let (output_token, received) = net.iter()
+        .filter(|(_, amount)| **amount > 0)
+        .max_by_key(|(_, amount)| **amount)
+        .map(|(mint, amount)| (*mint, *amount as u64))?;
+
+    // price保持单条成交的含义：计价代币最小单位/代币最小单位
+    let (quote_amount, token_amount, token) = if quotes.contains(&input_token) {
+        (spent, received, output_token)
+    } else if quotes.contains(&output_token) {
+        (received, spent, input_token)
+    } else {
+        (spent, received, output_token)
+    };
+    let price = if token_amount > 0 { quote_amount as f64 / token_amount as f64 } else { 0.0 };
+
+    let first = &legs[0];
+    Some(TradeDetails {
+        signature: first.signature.clone(),
+        wallet: first.wallet,
+        dex_program: first.dex_program.clone(),
+        dex: first.dex,
+        input_token,
+        output_token,
+        amount_in: spent,
+        amount_out: received,
+        price,
+        timestamp: first.timestamp,
+        token_decimals: decimals.get(&token).copied(),
+        price_usd: None,
+        value_usd: None,
+        timeline: first.timeline.clone(),
+        costs: first.costs,
+        skip_reason: None,
+        annotation: None,
+        sell_fraction: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline, USDC_MINT, WSOL_MINT};
+
+    fn leg(input: Pubkey, output: Pubkey, amount_in: u64, amount_out: u64) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: input,
+            output_token: output,
+            amount_in,
+            amount_out,
+            price: 0.0,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn split_route_merges_into_one_buy() {
+        let wsol: Pubkey = WSOL_MINT.parse().unwrap();
+        let mint = Pubkey::new_unique();
+        let trade = aggregate_legs(vec![leg(wsol, mint, 1_000, 400), leg(wsol, mint, 500, 200)], &QuoteMints::default()).unwrap();
+
+        assert_eq!((trade.input_token, trade.output_token), (wsol, mint));
+        assert_eq!((trade.amount_in, trade.amount_out), (1_500, 600));
+        assert_eq!(trade.price, 2.5);
+        assert_eq!(trade.token_decimals, Some(6));
+    }
+
+    #[test]
+    fn sell_then_buy_nets_to_token_swap() {
+        let wsol: Pubkey = WSOL_MINT.parse().unwrap();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let trade = aggregate_legs(vec![leg(a, wsol, 300, 1_000), leg(wsol, b, 1_000, 50)], &QuoteMints::default()).unwrap();
+
+        assert_eq!((trade.input_token, trade.output_token), (a, b));
+        assert_eq!((trade.amount_in, trade.amount_out), (300, 50));
+    }
+
+    #[test]
+    fn usdc_quoted_sell_prices_in_usdc() {
+        let usdc: Pubkey = USDC_MINT.parse().unwrap();
+        let mint = Pubkey::new_unique();
+        let trade = aggregate_legs(vec![leg(mint, usdc, 100, 2_000), leg(mint, usdc, 100, 1_000)], &QuoteMints::default()).unwrap();
+
+        assert_eq!((trade.input_token, trade.output_token), (mint, usdc));
+        assert_eq!(trade.price, 15.0);
+    }
+
+    #[test]
+    fn round_trip_cancels_out() {
+        let wsol: Pubkey = WSOL_MINT.parse().unwrap();
+        let mint = Pubkey::new_unique();
+        assert!(aggregate_legs(vec![leg(wsol, mint, 1_000, 400), leg(mint, wsol, 400, 1_000)], &QuoteMints::default()).is_none());
+    }
+}
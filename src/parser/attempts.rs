@@ -0,0 +1,103 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use crate::types::DexType;
+use super::pump;
+use super::raydium::RaydiumSwap;
+
+// Pump.fun buy/sell指令账户: mint(2) ... user(6)
+const PUMP_MINT_INDEX: usize = 2;
+const PUMP_USER_INDEX: usize = 6;
+
+// Raydium swap_base_in指令标签
+const RAYDIUM_SWAP_BASE_IN: u8 = 9;
+
+// 失败交易中尝试的成交；数量来自指令参数(数量与滑点限制)，不是实际成交
+#[derive(Debug, Clone, Serialize)]
+pub struct AttemptedTrade {
+    pub signature: String,
+    pub wallet: Pubkey,
+    pub dex: DexType,
+    pub input_token: Pubkey,
+    pub output_token: Pubkey,
+    // 买入时为最多花费，卖出时为卖出数量
+    pub amount_in: u64,
+    // 买入时为买入数量，卖出时为最少收到
+    pub amount_out: u64,
+    pub error: String,
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+// buy: amount(代币) + max_sol_cost；sell: amount(代币) + min_sol_output
+pub fn pump_attempt(signature: &str, data: &[u8], accounts: &[Pubkey], error: &str) -> Option<AttemptedTrade> {
+    let is_buy = pump::is_buy_instruction(data)?;
+    let amount = read_u64(data, 8)?;
+    let sol_limit = read_u64(data, 16)?;
+    let mint = *accounts.get(PUMP_MINT_INDEX)?;
+    let wsol = spl_token::native_mint::id();
+    let (input_token, output_token, amount_in, amount_out) = if is_buy {
+        (wsol, mint, sol_limit, amount)
+    } else {
+        (mint, wsol, amount, sol_limit)
+    };
+    Some(AttemptedTrade {
+        signature: signature.to_string(),
+        wallet: *accounts.get(PUMP_USER_INDEX)?,
+        dex: DexType::PumpFun,
+        input_token,
+        output_token,
+        amount_in,
+        amount_out,
+        error: error.to_string(),
+    })
+}
+
+// swap_base_in: amount_in + minimum_amount_out；swap_base_out: max_amount_in + amount_out
+pub fn raydium_attempt(
+    signature: &str,
+    data: &[u8],
+    swap: &RaydiumSwap,
+    (input_token, output_token): (Pubkey, Pubkey),
+    error: &str,
+) -> Option<AttemptedTrade> {
+    let amount = read_u64(data, 1)?;
+    let limit = read_u64(data, 9)?;
+    let (amount_in, amount_out) = if data.first() == Some(&RAYDIUM_SWAP_BASE_IN) { (amount, limit) } else { (limit, amount) };
+    Some(AttemptedTrade {
+        signature: signature.to_string(),
+        wallet: swap.user,
+        dex: DexType::Raydium,
+        input_token,
+        output_token,
+        amount_in,
+        amount_out,
+        error: error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_instruction_limits() {
+        let accounts: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+        let mut data = pump::BUY_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        let wsol = spl_token::native_mint::id();
+
+        let buy = pump_attempt("sig", &data, &accounts, "slippage").unwrap();
+        assert_eq!((buy.wallet, buy.input_token, buy.output_token), (accounts[6], wsol, accounts[2]));
+        assert_eq!((buy.amount_in, buy.amount_out), (250_000_000, 1_000_000));
+
+        let swap = RaydiumSwap { user: accounts[0], source_account: accounts[1], destination_account: accounts[2] };
+        let mut data = vec![11u8];
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&700u64.to_le_bytes());
+        let attempt = raydium_attempt("sig", &data, &swap, (wsol, accounts[3]), "slippage").unwrap();
+        assert_eq!((attempt.amount_in, attempt.amount_out), (700, 500));
+    }
+}
@@ -0,0 +1,101 @@
+use yellowstone_grpc_proto::prelude::{TokenBalance, TransactionStatusMeta};
+use crate::token_program::TokenProgram;
+
+// 单个代币账户在交易前后的余额(原始单位)，每笔交易只从meta构建一次，供各解析器共用
+#[derive(Debug, Clone)]
+pub struct TokenBalanceChange {
+    pub account_index: u32,
+    pub mint: String,
+    pub owner: String,
+    pub program: Option<TokenProgram>,
+    pub decimals: u8,
+    pub pre: Option<u64>,
+    pub post: Option<u64>,
+}
+
+impl TokenBalanceChange {
+    // 有符号变化，交易中新建或关闭的账户另一侧视为0
+    pub fn delta(&self) -> i128 {
+        self.post.unwrap_or(0) as i128 - self.pre.unwrap_or(0) as i128
+    }
+}
+
+// 按账户序号合并交易前后的代币余额记录
+pub fn token_balance_changes(meta: &TransactionStatusMeta) -> Vec<TokenBalanceChange> {
+    let mut changes: Vec<TokenBalanceChange> = Vec::with_capacity(meta.post_token_balances.len());
+    let mut apply = |balance: &TokenBalance, is_post: bool| {
+        let amount = balance.ui_token_amount.as_ref().and_then(|ui| ui.amount.parse::<u64>().ok());
+        let position = changes.iter().position(|c| c.account_index == balance.account_index);
+        let change = match position {
+            Some(position) => &mut changes[position],
+            None => {
+                changes.push(TokenBalanceChange {
+                    account_index: balance.account_index,
+                    mint: balance.mint.clone(),
+                    owner: balance.owner.clone(),
+                    program: TokenProgram::from_program_id(&balance.program_id),
+                    decimals: balance.ui_token_amount.as_ref().map_or(0, |ui| ui.decimals as u8),
+                    pre: None,
+                    post: None,
+                });
+                changes.last_mut().expect("just pushed")
+            }
+        };
+        if is_post {
+            change.post = amount;
+        } else {
+            change.pre = amount;
+        }
+    };
+    for balance in &meta.pre_token_balances {
+        apply(balance, false);
+    }
+    for balance in &meta.post_token_balances {
+        apply(balance, true);
+    }
+    changes.sort_by_key(|c| c.account_index);
+    changes
+}
+
+// 按账户序号查找
+pub fn find_change(changes: &[TokenBalanceChange], account_index: usize) -> Option<&TokenBalanceChange> {
+    changes.iter().find(|c| c.account_index as usize == account_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::UiTokenAmount;
+
+    fn balance(account_index: u32, amount: &str) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            mint: "mint".to_string(),
+            ui_token_amount: Some(UiTokenAmount {
+                amount: amount.to_string(),
+                decimals: 9,
+                ..Default::default()
+            }),
+            owner: "owner".to_string(),
+            program_id: spl_token::id().to_string(),
+        }
+    }
+
+    #[test]
+    fn merges_pre_and_post_by_account_index() {
+        let meta = TransactionStatusMeta {
+            pre_token_balances: vec![balance(3, "500"), balance(1, "18446744073709551615")],
+            post_token_balances: vec![balance(1, "18446744073709551000"), balance(5, "42")],
+            ..Default::default()
+        };
+
+        let changes = token_balance_changes(&meta);
+        let indexes: Vec<u32> = changes.iter().map(|c| c.account_index).collect();
+        assert_eq!(indexes, vec![1, 3, 5]);
+        assert_eq!(find_change(&changes, 1).unwrap().delta(), -615);
+        // 交易中关闭/新建的账户
+        assert_eq!(find_change(&changes, 3).unwrap().delta(), -500);
+        assert_eq!(find_change(&changes, 5).unwrap().delta(), 42);
+        assert_eq!(find_change(&changes, 5).unwrap().program, Some(TokenProgram::Spl));
+    }
+}
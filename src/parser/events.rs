@@ -0,0 +1,26 @@
+use base64::Engine;
+use borsh::BorshDeserialize;
+use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+
+// emit_cpi!通过自调用指令发出事件时，指令数据以此标签开头
+pub const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+// emit!写入日志的事件："Program data: <base64>"
+pub fn program_data(meta: &TransactionStatusMeta) -> impl Iterator<Item = Vec<u8>> + '_ {
+    meta.log_messages.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+}
+
+// 自调用指令数据去掉标签后即为事件数据
+pub fn cpi_event_data(ix_data: &[u8]) -> Option<&[u8]> {
+    ix_data.strip_prefix(&EVENT_IX_TAG)
+}
+
+// 事件数据 = 8字节鉴别器 + borsh编码的结构体；新版本追加的字段忽略
+pub fn decode_event<T: BorshDeserialize>(data: &[u8], discriminator: &[u8; 8]) -> Option<T> {
+    let mut body = data.strip_prefix(discriminator)?;
+    T::deserialize(&mut body).ok()
+}
@@ -0,0 +1,92 @@
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{Message, TransactionStatusMeta};
+use crate::types::TradeCosts;
+
+// 每个签名的基础费用(lamports)
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+// System Program transfer指令: u32类型(2) + u64 lamports
+const SYSTEM_TRANSFER_TAG: [u8; 4] = [2, 0, 0, 0];
+
+// Jito小费账户
+const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+pub fn is_jito_tip_account(account: &Pubkey) -> bool {
+    JITO_TIP_ACCOUNTS.iter().any(|tip| *tip == account.to_string())
+}
+
+// meta.fee = 基础签名费 + 优先费；小费是转给Jito小费账户的SOL(含CPI内部转账)
+pub fn trade_costs(message: &Message, account_keys: &[Pubkey], meta: &TransactionStatusMeta) -> TradeCosts {
+    let signatures = message.header.as_ref().map_or(1, |h| h.num_required_signatures as u64);
+    let base_fee = (signatures * LAMPORTS_PER_SIGNATURE).min(meta.fee);
+
+    let system_program = solana_sdk::system_program::id();
+    let outer = message.instructions.iter()
+        .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+    let inner = meta.inner_instructions.iter()
+        .flat_map(|inner| inner.instructions.iter())
+        .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+    let tip = outer.chain(inner)
+        .filter(|(program_id_index, _, _)| account_keys.get(*program_id_index as usize) == Some(&system_program))
+        .filter_map(|(_, accounts, data)| {
+            let lamports = data.strip_prefix(&SYSTEM_TRANSFER_TAG)?.get(..8)?;
+            let destination = account_keys.get(*accounts.get(1)? as usize)?;
+            is_jito_tip_account(destination).then(|| u64::from_le_bytes(lamports.try_into().unwrap_or_default()))
+        })
+        .sum();
+
+    TradeCosts {
+        base_fee,
+        priority_fee: meta.fee - base_fee,
+        tip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::{CompiledInstruction, InnerInstruction, InnerInstructions, MessageHeader};
+
+    fn transfer(lamports: u64) -> Vec<u8> {
+        [SYSTEM_TRANSFER_TAG.to_vec(), lamports.to_le_bytes().to_vec()].concat()
+    }
+
+    #[test]
+    fn splits_fee_and_sums_tips() {
+        let payer = Pubkey::new_unique();
+        let tip_account: Pubkey = JITO_TIP_ACCOUNTS[2].parse().unwrap();
+        let other = Pubkey::new_unique();
+        let keys = vec![payer, tip_account, other, solana_sdk::system_program::id()];
+
+        let message = Message {
+            header: Some(MessageHeader { num_required_signatures: 1, ..Default::default() }),
+            instructions: vec![
+                CompiledInstruction { program_id_index: 3, accounts: vec![0, 1], data: transfer(100_000) },
+                // 普通转账不计入小费
+                CompiledInstruction { program_id_index: 3, accounts: vec![0, 2], data: transfer(7_000_000) },
+            ],
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            fee: 25_000,
+            inner_instructions: vec![InnerInstructions {
+                index: 0,
+                instructions: vec![InnerInstruction { program_id_index: 3, accounts: vec![0, 1], data: transfer(50_000), stack_height: Some(2) }],
+            }],
+            ..Default::default()
+        };
+
+        let costs = trade_costs(&message, &keys, &meta);
+        assert_eq!((costs.base_fee, costs.priority_fee, costs.tip), (5_000, 20_000, 150_000));
+        assert_eq!(costs.total(), 175_000);
+    }
+}
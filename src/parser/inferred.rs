@@ -0,0 +1,153 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+use crate::types::{DexType, TradeCosts, TradeDetails, TradeTimeline};
+use super::balances::TokenBalanceChange;
+
+// 没有解析器匹配时，仅凭钱包在交易前后的SOL和代币余额变化推断成交
+// SOL(含WSOL)的变化可能混有手续费、租金，只在确定买卖方向时使用：
+// 买入 = SOL减少 + 恰好一个代币增加；卖出 = 恰好一个代币减少 + SOL增加；
+// 代币互换 = 恰好一个代币减少 + 恰好一个代币增加(忽略SOL)
+pub fn infer_trade(
+    signature: &str,
+    wallet: &Pubkey,
+    account_keys: &[Pubkey],
+    meta: &TransactionStatusMeta,
+    balances: &[TokenBalanceChange],
+) -> Option<TradeDetails> {
+    let wsol = spl_token::native_mint::id();
+    let wallet_str = wallet.to_string();
+
+    let mut token_deltas: HashMap<Pubkey, (i128, u8)> = HashMap::new();
+    for change in balances.iter().filter(|c| c.owner == wallet_str) {
+        if let Ok(mint) = change.mint.parse::<Pubkey>() {
+            token_deltas.entry(mint).or_insert((0, change.decimals)).0 += change.delta();
+        }
+    }
+
+    // 原生SOL变化，手续费支付者需要加回手续费
+    let index = account_keys.iter().position(|k| k == wallet)?;
+    let mut sol_delta = *meta.post_balances.get(index)? as i128 - *meta.pre_balances.get(index)? as i128;
+    if index == 0 {
+        sol_delta += meta.fee as i128;
+    }
+    sol_delta += token_deltas.remove(&wsol).map_or(0, |(delta, _)| delta);
+
+    let decreased: Vec<_> = token_deltas.iter().filter(|(_, (d, _))| *d < 0).collect();
+    let increased: Vec<_> = token_deltas.iter().filter(|(_, (d, _))| *d > 0).collect();
+    let (input_token, amount_in, output_token, amount_out, token_decimals) = match (decreased.as_slice(), increased.as_slice()) {
+        ([], [(mint, (delta, decimals))]) if sol_delta < 0 => (wsol, sol_delta.unsigned_abs(), **mint, *delta as u128, *decimals),
+        ([(mint, (delta, decimals))], []) if sol_delta > 0 => (**mint, delta.unsigned_abs(), wsol, sol_delta as u128, *decimals),
+        ([(in_mint, (in_delta, _))], [(out_mint, (out_delta, decimals))]) => {
+            (**in_mint, in_delta.unsigned_abs(), **out_mint, *out_delta as u128, *decimals)
+        }
+        _ => return None,
+    };
+
+    // 与解析器一致：以SOL计价时price为lamports/代币最小单位，代币互换时为输入/输出最小单位之比
+    let price = if output_token == wsol {
+        amount_out as f64 / amount_in as f64
+    } else {
+        amount_in as f64 / amount_out as f64
+    };
+
+    Some(TradeDetails {
+        signature: signature.to_string(),
+        wallet: *wallet,
+        dex_program: String::new(),
+        dex: DexType::Inferred,
+        input_token,
+        output_token,
+        amount_in: amount_in as u64,
+        amount_out: amount_out as u64,
+        price,
+        timestamp: chrono::Utc::now().timestamp(),
+        token_decimals: Some(token_decimals),
+        price_usd: None,
+        value_usd: None,
+        timeline: TradeTimeline::default(),
+        costs: TradeCosts::default(),
+        skip_reason: None,
+        annotation: None,
+        sell_fraction: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::balances::token_balance_changes;
+    use yellowstone_grpc_proto::prelude::{TokenBalance, UiTokenAmount};
+
+    fn balance(account_index: u32, mint: &Pubkey, owner: &Pubkey, amount: u64) -> TokenBalance {
+        TokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount {
+                amount: amount.to_string(),
+                decimals: 6,
+                ..Default::default()
+            }),
+            owner: owner.to_string(),
+            program_id: spl_token::id().to_string(),
+        }
+    }
+
+    #[test]
+    fn infers_buy_and_adds_back_fee() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let keys = vec![wallet, Pubkey::new_unique()];
+        let meta = TransactionStatusMeta {
+            fee: 5_000,
+            pre_balances: vec![2_000_000_000, 0],
+            post_balances: vec![999_995_000, 0],
+            pre_token_balances: vec![],
+            post_token_balances: vec![balance(1, &mint, &wallet, 4_000)],
+            ..Default::default()
+        };
+
+        let trade = infer_trade("sig", &wallet, &keys, &meta, &token_balance_changes(&meta)).unwrap();
+        assert_eq!(trade.dex, DexType::Inferred);
+        assert_eq!((trade.input_token, trade.output_token), (spl_token::native_mint::id(), mint));
+        assert_eq!((trade.amount_in, trade.amount_out), (1_000_000_000, 4_000));
+        assert_eq!(trade.token_decimals, Some(6));
+    }
+
+    #[test]
+    fn infers_token_swap_without_sol_leg() {
+        let wallet = Pubkey::new_unique();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = vec![wallet, Pubkey::new_unique(), Pubkey::new_unique()];
+        let meta = TransactionStatusMeta {
+            fee: 5_000,
+            pre_balances: vec![1_000_000_000, 0, 0],
+            post_balances: vec![999_995_000, 0, 0],
+            pre_token_balances: vec![balance(1, &a, &wallet, 9_000)],
+            post_token_balances: vec![balance(1, &a, &wallet, 1_000), balance(2, &b, &wallet, 2_000)],
+            ..Default::default()
+        };
+
+        let trade = infer_trade("sig", &wallet, &keys, &meta, &token_balance_changes(&meta)).unwrap();
+        assert_eq!((trade.input_token, trade.output_token), (a, b));
+        assert_eq!((trade.amount_in, trade.amount_out), (8_000, 2_000));
+        assert_eq!(trade.price, 4.0);
+    }
+
+    #[test]
+    fn plain_transfer_is_not_a_trade() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let keys = vec![wallet, Pubkey::new_unique()];
+        let meta = TransactionStatusMeta {
+            fee: 5_000,
+            pre_balances: vec![1_000_000_000, 0],
+            post_balances: vec![999_995_000, 0],
+            pre_token_balances: vec![balance(1, &mint, &wallet, 4_000)],
+            post_token_balances: vec![balance(1, &mint, &wallet, 1_000)],
+            ..Default::default()
+        };
+
+        assert!(infer_trade("sig", &wallet, &keys, &meta, &token_balance_changes(&meta)).is_none());
+    }
+}
@@ -0,0 +1,65 @@
+use solana_sdk::pubkey::Pubkey;
+
+// Lifinity v2 程序地址
+pub const LIFINITY_V2_PROGRAM: &str = "2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c";
+
+// Anchor指令鉴别器 sha256("global:swap")[..8]
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// swap指令中的账户位置
+const USER_AUTHORITY_INDEX: usize = 2;
+const SOURCE_ACCOUNT_INDEX: usize = 3;
+const DESTINATION_ACCOUNT_INDEX: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct LifinitySwap {
+    pub user: Pubkey,
+    pub source_account: Pubkey,
+    pub destination_account: Pubkey,
+}
+
+// 只比较鉴别器，供预过滤使用
+pub fn is_swap_instruction(data: &[u8]) -> bool {
+    data.len() >= 8 && data[..8] == SWAP_DISCRIMINATOR
+}
+
+// 解析swap指令，实际成交取自余额变化
+// 数据布局: discriminator(8) + amount_in(u64) + minimum_amount_out(u64)
+pub fn parse_swap_instruction(data: &[u8], accounts: &[Pubkey]) -> Option<LifinitySwap> {
+    if data.len() < 8 + 8 + 8 || data[..8] != SWAP_DISCRIMINATOR {
+        return None;
+    }
+
+    Some(LifinitySwap {
+        user: *accounts.get(USER_AUTHORITY_INDEX)?,
+        source_account: *accounts.get(SOURCE_ACCOUNT_INDEX)?,
+        destination_account: *accounts.get(DESTINATION_ACCOUNT_INDEX)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn discriminator_matches_anchor_sighash() {
+        let hash = Sha256::digest(b"global:swap");
+        assert_eq!(hash[..8], SWAP_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn parses_swap_accounts() {
+        let accounts: Vec<Pubkey> = (0..13).map(|_| Pubkey::new_unique()).collect();
+        let mut data = SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&990u64.to_le_bytes());
+
+        let swap = parse_swap_instruction(&data, &accounts).unwrap();
+        assert_eq!(swap.user, accounts[2]);
+        assert_eq!(swap.source_account, accounts[3]);
+        assert_eq!(swap.destination_account, accounts[4]);
+
+        assert!(parse_swap_instruction(&data[..20], &accounts).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use base64::Engine;
+use borsh::BorshDeserialize;
+use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+use crate::types::DexType;
+
+const LOG_PREFIX: &str = "Program log: ";
+
+// 日志中提取出的精确成交量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapLog {
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+// 每个DEX的成交日志格式：日志前缀 + 解码函数
+struct LogPattern {
+    dex: DexType,
+    prefix: &'static str,
+    decode: fn(&str) -> Option<SwapLog>,
+}
+
+const LOG_PATTERNS: &[LogPattern] = &[
+    LogPattern { dex: DexType::Raydium, prefix: "ray_log: ", decode: decode_ray_log },
+];
+
+// 按调用栈归属日志，返回program_id自己输出的"Program log:"内容(不含它调用的其他程序)
+pub fn program_logs<'a>(meta: &'a TransactionStatusMeta, program_id: &str) -> Vec<&'a str> {
+    let mut stack: Vec<&str> = Vec::new();
+    let mut logs = Vec::new();
+    for line in &meta.log_messages {
+        if let Some(message) = line.strip_prefix(LOG_PREFIX) {
+            if stack.last() == Some(&program_id) {
+                logs.push(message);
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("Program ") else { continue };
+        let mut words = rest.split_whitespace();
+        let (Some(program), Some(action)) = (words.next(), words.next()) else { continue };
+        match action {
+            "invoke" => stack.push(program),
+            "success" | "failed:" => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    logs
+}
+
+// 按顺序提取某个DEX程序输出的成交日志
+pub fn swap_logs(meta: &TransactionStatusMeta, dex: DexType, program_id: &str) -> Vec<SwapLog> {
+    let Some(pattern) = LOG_PATTERNS.iter().find(|p| p.dex == dex) else { return Vec::new() };
+    program_logs(meta, program_id).into_iter()
+        .filter_map(|log| log.strip_prefix(pattern.prefix))
+        .filter_map(pattern.decode)
+        .collect()
+}
+
+// Raydium AMM v4的ray_log: base64编码的定长结构，第一个字节为日志类型
+const RAY_LOG_SWAP_BASE_IN: u8 = 3;
+const RAY_LOG_SWAP_BASE_OUT: u8 = 4;
+
+#[derive(BorshDeserialize)]
+struct SwapBaseInLog {
+    amount_in: u64,
+    _minimum_out: u64,
+    _direction: u64,
+    _user_source: u64,
+    _pool_coin: u64,
+    _pool_pc: u64,
+    out_amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SwapBaseOutLog {
+    _max_in: u64,
+    amount_out: u64,
+    _direction: u64,
+    _user_source: u64,
+    _pool_coin: u64,
+    _pool_pc: u64,
+    deduct_in: u64,
+}
+
+fn decode_ray_log(data: &str) -> Option<SwapLog> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?;
+    let (log_type, mut body) = bytes.split_first()?;
+    match *log_type {
+        RAY_LOG_SWAP_BASE_IN => {
+            let log = SwapBaseInLog::deserialize(&mut body).ok()?;
+            Some(SwapLog { amount_in: log.amount_in, amount_out: log.out_amount })
+        }
+        RAY_LOG_SWAP_BASE_OUT => {
+            let log = SwapBaseOutLog::deserialize(&mut body).ok()?;
+            Some(SwapLog { amount_in: log.deduct_in, amount_out: log.amount_out })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAYDIUM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+    fn ray_log(log_type: u8, fields: [u64; 7]) -> String {
+        let mut bytes = vec![log_type];
+        for field in fields {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        format!("ray_log: {}", base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    #[test]
+    fn extracts_ray_logs_of_the_invoked_program_only() {
+        let meta = TransactionStatusMeta {
+            log_messages: vec![
+                "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+                "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+                format!("Program {} invoke [1]", RAYDIUM),
+                format!("Program log: {}", ray_log(3, [1_000_000, 900, 1, 5_000_000, 7, 8, 123_456])),
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+                "Program log: Instruction: Transfer".to_string(),
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 4645 of 180000 compute units".to_string(),
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+                format!("Program {} consumed 30000 of 200000 compute units", RAYDIUM),
+                format!("Program {} success", RAYDIUM),
+                format!("Program {} invoke [1]", RAYDIUM),
+                format!("Program log: {}", ray_log(4, [2_000, 77, 2, 3, 4, 5, 1_950])),
+                format!("Program {} success", RAYDIUM),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(program_logs(&meta, RAYDIUM).len(), 2);
+        assert_eq!(swap_logs(&meta, DexType::Raydium, RAYDIUM), vec![
+            SwapLog { amount_in: 1_000_000, amount_out: 123_456 },
+            SwapLog { amount_in: 1_950, amount_out: 77 },
+        ]);
+        assert!(swap_logs(&meta, DexType::Phoenix, RAYDIUM).is_empty());
+    }
+
+    #[test]
+    fn ignores_non_swap_ray_logs() {
+        // 类型0为初始化日志
+        assert!(decode_ray_log(ray_log(0, [0; 7]).trim_start_matches("ray_log: ")).is_none());
+        assert!(decode_ray_log("not base64").is_none());
+    }
+}
@@ -1,28 +1,693 @@
-use anyhow::Result;
-use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
-use crate::types::{TradeDetails, DexType};
+pub(crate) mod aggregate;
+pub mod attempts;
+pub(crate) mod balances;
+pub(crate) mod events;
+pub(crate) mod fees;
+pub(crate) mod inferred;
+pub mod lifinity;
+pub(crate) mod logs;
+pub mod moonshot;
+pub mod phoenix;
+pub mod pools;
+pub mod pump;
+pub mod raydium;
+pub mod transfers;
 
-pub struct TransactionParser;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use yellowstone_grpc_proto::convert_from;
+use yellowstone_grpc_proto::prelude::{Message, Transaction, TransactionStatusMeta};
+use self::balances::{find_change, token_balance_changes, TokenBalanceChange};
+use crate::error::ParseError;
+use crate::types::{LiquidityEvent, QuoteMints, TradeCosts, TradeDetails, TradeKind, TradeTimeline, TransferEvent, DexType};
+
+/// 把gRPC推送的交易解析为DEX成交([`TradeDetails`])、流动性操作或转账。
+///
+/// 不依赖网络，可单独使用。
+pub struct TransactionParser {
+    quotes: QuoteMints,
+}
+
+impl Default for TransactionParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl TransactionParser {
+    /// 使用默认计价代币集合(WSOL、USDC、USDT)
     pub fn new() -> Self {
-        TransactionParser
+        TransactionParser { quotes: QuoteMints::default() }
+    }
+    
+    /// 决定成交方向和price计价方的代币集合
+    pub fn with_quote_mints(mut self, quotes: QuoteMints) -> Self {
+        self.quotes = quotes;
+        self
     }
     
+    /// 有成交解析器的DEX程序，订阅时可作为account_required预过滤
+    pub const KNOWN_DEX_PROGRAMS: [&'static str; 5] = [
+        raydium::RAYDIUM_AMM_V4_PROGRAM,
+        pump::PUMP_PROGRAM,
+        moonshot::MOONSHOT_PROGRAM,
+        lifinity::LIFINITY_V2_PROGRAM,
+        phoenix::PHOENIX_PROGRAM,
+    ];
+
+    /// 按程序ID识别DEX，没有解析器的程序为[`DexType::Unknown`]
     pub fn identify_dex(&self, program_id: &str) -> DexType {
         match program_id {
-            "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8" => DexType::Raydium,
-            "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwdFi" => DexType::PumpFun,
+            raydium::RAYDIUM_AMM_V4_PROGRAM => DexType::Raydium,
+            pump::PUMP_PROGRAM => DexType::PumpFun,
+            moonshot::MOONSHOT_PROGRAM => DexType::Moonshot,
+            lifinity::LIFINITY_V2_PROGRAM => DexType::Lifinity,
+            phoenix::PHOENIX_PROGRAM => DexType::Phoenix,
             _ => DexType::Unknown,
         }
     }
     
+    /// 用已知DEX的解析器解析成交；交易中没有可识别的成交指令时返回`Ok(None)`
     pub fn parse_transaction(
         &self, 
-        _tx: &EncodedConfirmedTransactionWithStatusMeta  // 添加下划线前缀表示暂时未使用
-    ) -> Result<Option<TradeDetails>> {
-        // 这里添加实际的解析逻辑
-        // 现在只返回None作为占位
-        Ok(None)
+        signature: &str,
+        tx: &Transaction,
+        meta: Option<&TransactionStatusMeta>,
+    ) -> Result<Option<TradeDetails>, ParseError> {
+        let message = match &tx.message {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        
+        let account_keys = resolve_account_keys(message, meta)?;
+        let balances = meta.map(token_balance_changes).unwrap_or_default();
+        
+        // 顶层指令和CPI内部指令(路由器、机器人合约调用)都需要检查
+        let outer = message.instructions.iter()
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        let inner = meta.into_iter()
+            .flat_map(|m| m.inner_instructions.iter())
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        
+        // 同一交易可能包含多条成交指令，全部收集后合并为一笔净成交
+        let mut legs = Vec::new();
+        let mut settled_accounts = HashSet::new();
+        let mut pump_events = Vec::new();
+        let mut pump_instructions = 0;
+        let mut raydium_logs: Option<Vec<logs::SwapLog>> = None;
+        let mut raydium_swaps = 0;
+        for (program_id_index, ix_accounts, data) in outer.chain(inner) {
+            let program_id = match account_keys.get(program_id_index as usize) {
+                Some(program_id) => program_id.to_string(),
+                None => continue,
+            };
+            
+            let dex = self.identify_dex(&program_id);
+            if dex == DexType::Unknown {
+                continue;
+            }
+            let accounts = ix_accounts.iter()
+                .filter_map(|&i| account_keys.get(i as usize).copied())
+                .collect::<Vec<Pubkey>>();
+            
+            // 有meta时成交量取自余额变化，同一对账户上的多条指令已包含在一次变化里
+            match dex {
+                // Pump.fun成交量取自TradeEvent事件
+                DexType::PumpFun => {
+                    if let Some(event) = pump::parse_cpi_event(data) {
+                        pump_events.push(event);
+                    } else if pump::is_trade_instruction(data) {
+                        pump_instructions += 1;
+                    }
+                }
+                // 第N条Raydium swap对应该程序输出的第N条ray_log
+                DexType::Raydium => {
+                    if let Some(swap) = raydium::parse_swap_instruction(data, &accounts) {
+                        let exact = raydium_logs
+                            .get_or_insert_with(|| meta.map(|m| logs::swap_logs(m, dex, raydium::RAYDIUM_AMM_V4_PROGRAM)).unwrap_or_default())
+                            .get(raydium_swaps)
+                            .copied();
+                        raydium_swaps += 1;
+                        if exact.is_none() && !settled_accounts.insert((swap.source_account, swap.destination_account)) {
+                            continue;
+                        }
+                        legs.push(self.raydium_trade_details(signature, &program_id, swap, exact, &account_keys, &balances)?);
+                    }
+                }
+                DexType::Moonshot => {
+                    if let Some(trade) = moonshot::parse_trade_instruction(data, &accounts) {
+                        if meta.is_some() && !settled_accounts.insert((trade.sender_token_account, trade.curve_account)) {
+                            continue;
+                        }
+                        legs.push(self.moonshot_trade_details(signature, &program_id, trade, &account_keys, meta, &balances)?);
+                    }
+                }
+                DexType::Lifinity => {
+                    if let Some(swap) = lifinity::parse_swap_instruction(data, &accounts) {
+                        if !settled_accounts.insert((swap.source_account, swap.destination_account)) {
+                            continue;
+                        }
+                        let accounts = (swap.source_account, swap.destination_account);
+                        legs.push(self.token_account_trade_details(signature, &program_id, dex, swap.user, accounts, &account_keys, &balances)?);
+                    }
+                }
+                DexType::Phoenix => {
+                    if let Some(swap) = phoenix::parse_swap_instruction(data, &accounts) {
+                        if !settled_accounts.insert((swap.base_account, swap.quote_account)) {
+                            continue;
+                        }
+                        let accounts = (swap.base_account, swap.quote_account);
+                        legs.push(self.token_account_trade_details(signature, &program_id, dex, swap.trader, accounts, &account_keys, &balances)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        // 旧版程序没有自调用事件，从日志读取；都没有时交给余额推断兜底
+        if pump_events.is_empty() && pump_instructions > 0 {
+            if let Some(meta) = meta {
+                pump_events.extend(events::program_data(meta).filter_map(|data| pump::parse_log_event(&data)));
+            }
+        }
+        legs.extend(pump_events.into_iter().map(|event| self.pump_trade_details(signature, event)));
+        
+        let signers = signers(message, &account_keys);
+        for leg in &mut legs {
+            attribute_to_signer(leg, signers);
+        }
+        let mut trade = aggregate::aggregate_legs(legs, &self.quotes);
+        if let (Some(trade), Some(meta)) = (trade.as_mut(), meta) {
+            trade.costs = fees::trade_costs(message, &account_keys, meta);
+        }
+        Ok(trade)
+    }
+    
+    /// 新建池子/代币的指令(顶层和CPI)
+    pub fn new_pools(&self, signature: &str, tx: &Transaction, meta: Option<&TransactionStatusMeta>) -> Vec<pools::NewPool> {
+        let Some(message) = &tx.message else { return Vec::new() };
+        let Ok(account_keys) = resolve_account_keys(message, meta) else { return Vec::new() };
+        
+        let outer = message.instructions.iter()
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        let inner = meta.into_iter()
+            .flat_map(|m| m.inner_instructions.iter())
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        
+        outer.chain(inner)
+            .filter_map(|(program_id_index, ix_accounts, data)| {
+                let program_id = account_keys.get(program_id_index as usize)?;
+                let accounts = ix_accounts.iter()
+                    .filter_map(|&i| account_keys.get(i as usize).copied())
+                    .collect::<Vec<Pubkey>>();
+                pools::parse_new_pool(signature, self.identify_dex(&program_id.to_string()), data, &accounts)
+            })
+            .collect()
+    }
+    
+    /// 失败交易中尝试的成交，取第一条Pump.fun/Raydium成交指令的参数
+    pub fn parse_attempt(&self, signature: &str, tx: &Transaction, meta: &TransactionStatusMeta) -> Option<attempts::AttemptedTrade> {
+        let message = tx.message.as_ref()?;
+        let account_keys = resolve_account_keys(message, Some(meta)).ok()?;
+        let error = convert_from::create_tx_error(meta.err.as_ref()).ok().flatten()
+            .map_or_else(|| "unknown error".to_string(), |e| e.to_string());
+        let balances = token_balance_changes(meta);
+        let wsol = spl_token::native_mint::id();
+        
+        let outer = message.instructions.iter()
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        let inner = meta.inner_instructions.iter()
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        for (program_id_index, ix_accounts, data) in outer.chain(inner) {
+            let Some(program_id) = account_keys.get(program_id_index as usize) else { continue };
+            let accounts = ix_accounts.iter()
+                .filter_map(|&i| account_keys.get(i as usize).copied())
+                .collect::<Vec<Pubkey>>();
+            let attempt = match self.identify_dex(&program_id.to_string()) {
+                DexType::PumpFun => attempts::pump_attempt(signature, data, &accounts, &error),
+                DexType::Raydium => raydium::parse_swap_instruction(data, &accounts).and_then(|swap| {
+                    // 失败交易余额不变，但交易前的代币余额记录仍给出账户对应的代币
+                    let mint_of = |account: &Pubkey| account_change(&balances, &account_keys, account)
+                        .and_then(|change| change.mint.parse().ok())
+                        .unwrap_or(wsol);
+                    let mints = (mint_of(&swap.source_account), mint_of(&swap.destination_account));
+                    attempts::raydium_attempt(signature, data, &swap, mints, &error)
+                }),
+                _ => None,
+            };
+            if attempt.is_some() {
+                return attempt;
+            }
+        }
+        None
+    }
+    
+    /// 预过滤：只看程序ID和指令前几个字节，判断是否可能包含可解析的成交
+    pub fn has_trade_instruction(&self, tx: &Transaction, meta: Option<&TransactionStatusMeta>) -> bool {
+        let Some(message) = &tx.message else { return false };
+        let Ok(account_keys) = resolve_account_keys(message, meta) else { return false };
+        
+        let outer = message.instructions.iter()
+            .map(|ix| (ix.program_id_index, &ix.data));
+        let inner = meta.into_iter()
+            .flat_map(|m| m.inner_instructions.iter())
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|ix| (ix.program_id_index, &ix.data));
+        
+        outer.chain(inner).any(|(program_id_index, data)| {
+            let Some(program_id) = account_keys.get(program_id_index as usize) else { return false };
+            match self.identify_dex(&program_id.to_string()) {
+                DexType::PumpFun => pump::is_trade_instruction(data),
+                DexType::Raydium => raydium::is_swap_instruction(data),
+                DexType::Moonshot => moonshot::is_trade_instruction(data),
+                DexType::Lifinity => lifinity::is_swap_instruction(data),
+                DexType::Phoenix => phoenix::is_swap_instruction(data),
+                _ => false,
+            }
+        })
+    }
+    
+    /// 解析成交，没有解析器匹配时由余额变化推断；流动性操作和普通转账的余额变化类似成交，先单独识别
+    pub fn classify(
+        &self,
+        signature: &str,
+        tx: &Transaction,
+        meta: Option<&TransactionStatusMeta>,
+        wallet: &Pubkey,
+    ) -> Result<Option<TradeKind>, ParseError> {
+        if let Some(event) = self.liquidity_event(signature, tx, meta, wallet) {
+            return Ok(Some(TradeKind::Liquidity(event)));
+        }
+        if let Some(trade) = self.parse_transaction(signature, tx, meta)? {
+            return Ok(Some(TradeKind::Swap(trade)));
+        }
+        let Some(meta) = meta else { return Ok(None) };
+        if let Some(transfer) = self.transfer(signature, tx, meta, wallet) {
+            return Ok(Some(TradeKind::Transfer(transfer)));
+        }
+        Ok(self.infer_trade(signature, tx, meta, wallet).map(TradeKind::Swap))
+    }
+    
+    fn transfer(&self, signature: &str, tx: &Transaction, meta: &TransactionStatusMeta, wallet: &Pubkey) -> Option<TransferEvent> {
+        let message = tx.message.as_ref()?;
+        let account_keys = resolve_account_keys(message, Some(meta)).ok()?;
+        transfers::classify_transfer(signature, wallet, &account_keys, message, meta, &token_balance_changes(meta))
+    }
+    
+    /// Raydium AMM的deposit/withdraw指令(顶层或CPI)
+    pub fn liquidity_event(
+        &self,
+        signature: &str,
+        tx: &Transaction,
+        meta: Option<&TransactionStatusMeta>,
+        wallet: &Pubkey,
+    ) -> Option<LiquidityEvent> {
+        let message = tx.message.as_ref()?;
+        let account_keys = resolve_account_keys(message, meta).ok()?;
+        let outer = message.instructions.iter()
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        let inner = meta.into_iter()
+            .flat_map(|m| m.inner_instructions.iter())
+            .flat_map(|inner| inner.instructions.iter())
+            .map(|ix| (ix.program_id_index, &ix.accounts, &ix.data));
+        
+        let (action, pool) = outer.chain(inner).find_map(|(program_id_index, accounts, data)| {
+            let program_id = account_keys.get(program_id_index as usize)?.to_string();
+            if program_id != raydium::RAYDIUM_AMM_V4_PROGRAM {
+                return None;
+            }
+            let action = raydium::liquidity_action(data)?;
+            let pool = account_keys.get(*accounts.get(raydium::LIQUIDITY_AMM_INDEX)? as usize)?;
+            Some((action, *pool))
+        })?;
+        
+        let wallet_str = wallet.to_string();
+        let token_changes = meta.map(token_balance_changes).unwrap_or_default().into_iter()
+            .filter(|change| change.owner == wallet_str && change.delta() != 0)
+            .map(|change| (change.mint.clone(), change.delta()))
+            .collect();
+        Some(LiquidityEvent {
+            signature: signature.to_string(),
+            wallet: *wallet,
+            dex: DexType::Raydium,
+            action,
+            pool,
+            token_changes,
+        })
+    }
+    
+    /// 没有解析器匹配时由钱包余额变化推断成交，dex_program取第一个非系统类顶层程序
+    pub fn infer_trade(
+        &self,
+        signature: &str,
+        tx: &Transaction,
+        meta: &TransactionStatusMeta,
+        wallet: &Pubkey,
+    ) -> Option<TradeDetails> {
+        let message = tx.message.as_ref()?;
+        let account_keys = resolve_account_keys(message, Some(meta)).ok()?;
+        let balances = token_balance_changes(meta);
+        let mut trade = inferred::infer_trade(signature, wallet, &account_keys, meta, &balances)?;
+        
+        let infrastructure = [
+            solana_sdk::system_program::id(),
+            solana_sdk::compute_budget::id(),
+            spl_token::id(),
+            spl_associated_token_account::id(),
+        ];
+        trade.dex_program = message.instructions.iter()
+            .filter_map(|ix| account_keys.get(ix.program_id_index as usize))
+            .find(|program| !infrastructure.contains(program))
+            .map(|program| program.to_string())
+            .unwrap_or_default();
+        trade.costs = fees::trade_costs(message, &account_keys, meta);
+        Some(trade)
+    }
+    
+    fn pump_trade_details(&self, signature: &str, event: pump::TradeEvent) -> TradeDetails {
+        let wsol = spl_token::native_mint::id();
+        let (input_token, output_token, amount_in, amount_out) = if event.is_buy {
+            (wsol, event.mint, event.sol_amount, event.token_amount)
+        } else {
+            (event.mint, wsol, event.token_amount, event.sol_amount)
+        };
+        let price = if event.token_amount > 0 {
+            event.sol_amount as f64 / event.token_amount as f64
+        } else {
+            0.0
+        };
+        
+        TradeDetails {
+            signature: signature.to_string(),
+            wallet: event.user,
+            dex_program: pump::PUMP_PROGRAM.to_string(),
+            dex: DexType::PumpFun,
+            input_token,
+            output_token,
+            amount_in,
+            amount_out,
+            price,
+            timestamp: event.timestamp,
+            token_decimals: Some(pump::PUMP_TOKEN_DECIMALS),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+    
+    fn moonshot_trade_details(
+        &self,
+        signature: &str,
+        program_id: &str,
+        trade: moonshot::MoonshotTrade,
+        account_keys: &[Pubkey],
+        meta: Option<&TransactionStatusMeta>,
+        balances: &[TokenBalanceChange],
+    ) -> Result<TradeDetails, ParseError> {
+        if moonshot::derive_curve_account(&trade.mint) != trade.curve_account {
+            return Err(ParseError::CurveMismatch { dex: "Moonshot", mint: trade.mint.to_string() });
+        }
+        
+        // 实际成交量取自余额变化；指令参数只有精确一侧可以作为兜底
+        let token_change = account_change(balances, account_keys, &trade.sender_token_account);
+        let token_filled = token_change
+            .map(|change| change.delta().unsigned_abs() as u64)
+            .or_else(|| trade.exact_token_amount())
+            .ok_or_else(|| ParseError::MissingFill { dex: "Moonshot", side: "token", signature: signature.to_string() })?;
+        let collateral_filled = meta
+            .and_then(|m| lamport_delta(m, account_keys, &trade.curve_account))
+            .or_else(|| trade.exact_collateral_amount())
+            .ok_or_else(|| ParseError::MissingFill { dex: "Moonshot", side: "SOL", signature: signature.to_string() })?;
+        
+        let token_decimals = token_change.map(|change| change.decimals);
+        
+        let wsol = spl_token::native_mint::id();
+        let (input_token, output_token, amount_in, amount_out) = match trade.side {
+            moonshot::MoonshotSide::Buy => (wsol, trade.mint, collateral_filled, token_filled),
+            moonshot::MoonshotSide::Sell => (trade.mint, wsol, token_filled, collateral_filled),
+        };
+        
+        let price = if token_filled > 0 {
+            collateral_filled as f64 / token_filled as f64
+        } else {
+            0.0
+        };
+        
+        Ok(TradeDetails {
+            signature: signature.to_string(),
+            wallet: trade.sender,
+            dex_program: program_id.to_string(),
+            dex: DexType::Moonshot,
+            input_token,
+            output_token,
+            amount_in,
+            amount_out,
+            price,
+            timestamp: chrono::Utc::now().timestamp(),
+            token_decimals,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        })
+    }
+    
+    // 数量优先取ray_log；临时WSOL账户在交易内创建又关闭，没有余额记录，缺失的一侧视为WSOL
+    fn raydium_trade_details(
+        &self,
+        signature: &str,
+        program_id: &str,
+        swap: raydium::RaydiumSwap,
+        exact: Option<logs::SwapLog>,
+        account_keys: &[Pubkey],
+        balances: &[TokenBalanceChange],
+    ) -> Result<TradeDetails, ParseError> {
+        let missing_fill = || ParseError::MissingFill { dex: "Raydium", side: "token", signature: signature.to_string() };
+        let source = account_change(balances, account_keys, &swap.source_account);
+        let destination = account_change(balances, account_keys, &swap.destination_account);
+        if source.is_none() && destination.is_none() {
+            return Err(missing_fill());
+        }
+        
+        let wsol = spl_token::native_mint::id();
+        let mint_of = |change: Option<&TokenBalanceChange>| match change {
+            Some(change) => change.mint.parse::<Pubkey>().map_err(|_| missing_fill()),
+            None => Ok(wsol),
+        };
+        let input_token = mint_of(source)?;
+        let output_token = mint_of(destination)?;
+        
+        let (amount_in, amount_out) = match (exact, source, destination) {
+            (Some(log), _, _) => (log.amount_in, log.amount_out),
+            (None, Some(source), Some(destination)) if source.delta() < 0 && destination.delta() > 0 => {
+                (source.delta().unsigned_abs() as u64, destination.delta() as u64)
+            }
+            _ => return Err(missing_fill()),
+        };
+        if amount_in == 0 || amount_out == 0 {
+            return Err(missing_fill());
+        }
+        
+        let (price, token_decimals) = if self.quotes.contains(&output_token) {
+            (amount_out as f64 / amount_in as f64, source.map(|c| c.decimals))
+        } else {
+            (amount_in as f64 / amount_out as f64, destination.map(|c| c.decimals))
+        };
+        
+        Ok(TradeDetails {
+            signature: signature.to_string(),
+            wallet: swap.user,
+            dex_program: program_id.to_string(),
+            dex: DexType::Raydium,
+            input_token,
+            output_token,
+            amount_in,
+            amount_out,
+            price,
+            timestamp: chrono::Utc::now().timestamp(),
+            token_decimals,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        })
+    }
+    
+    // 按用户两个代币账户的余额变化还原成交：减少的一侧为输入，增加的一侧为输出
+    #[allow(clippy::too_many_arguments)]
+    fn token_account_trade_details(
+        &self,
+        signature: &str,
+        program_id: &str,
+        dex: DexType,
+        wallet: Pubkey,
+        (account_a, account_b): (Pubkey, Pubkey),
+        account_keys: &[Pubkey],
+        balances: &[TokenBalanceChange],
+    ) -> Result<TradeDetails, ParseError> {
+        let missing_fill = || ParseError::MissingFill { dex: dex.name(), side: "token", signature: signature.to_string() };
+        let a = account_change(balances, account_keys, &account_a).ok_or_else(missing_fill)?;
+        let b = account_change(balances, account_keys, &account_b).ok_or_else(missing_fill)?;
+        let (input, output) = match (a.delta() < 0, b.delta() < 0) {
+            (true, false) if b.delta() > 0 => (a, b),
+            (false, true) if a.delta() > 0 => (b, a),
+            _ => return Err(missing_fill()),
+        };
+        let amount_in = input.delta().unsigned_abs() as u64;
+        let amount_out = output.delta() as u64;
+        let input_token: Pubkey = input.mint.parse().map_err(|_| missing_fill())?;
+        let output_token: Pubkey = output.mint.parse().map_err(|_| missing_fill())?;
+        
+        // 与其他DEX一致：price为计价代币最小单位/代币最小单位
+        let (price, token_decimals) = if self.quotes.contains(&output_token) {
+            (amount_out as f64 / amount_in as f64, Some(input.decimals))
+        } else {
+            (amount_in as f64 / amount_out as f64, Some(output.decimals))
+        };
+        
+        Ok(TradeDetails {
+            signature: signature.to_string(),
+            wallet,
+            dex_program: program_id.to_string(),
+            dex,
+            input_token,
+            output_token,
+            amount_in,
+            amount_out,
+            price,
+            timestamp: chrono::Utc::now().timestamp(),
+            token_decimals,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        })
+    }
+}
+
+// 静态账户 + 地址查找表加载的账户(可写在前，只读在后)
+pub(crate) fn resolve_account_keys(message: &Message, meta: Option<&TransactionStatusMeta>) -> Result<Vec<Pubkey>, ParseError> {
+    let loaded_keys = meta.into_iter()
+        .flat_map(|m| m.loaded_writable_addresses.iter().chain(m.loaded_readonly_addresses.iter()));
+    message.account_keys.iter()
+        .chain(loaded_keys)
+        .map(|k| Pubkey::try_from(k.as_slice()))
+        .collect::<Result<Vec<Pubkey>, _>>()
+        .map_err(|_| ParseError::InvalidAccountKey)
+}
+
+// 消息头中前num_required_signatures个账户为签名者，第一个为手续费支付者
+fn signers<'a>(message: &Message, account_keys: &'a [Pubkey]) -> &'a [Pubkey] {
+    let count = message.header.as_ref().map_or(1, |h| h.num_required_signatures as usize);
+    &account_keys[..count.max(1).min(account_keys.len())]
+}
+
+// 指令里的用户账户不是签名者时(路由器、机器人合约的PDA)，成交归属到手续费支付者
+fn attribute_to_signer(trade: &mut TradeDetails, signers: &[Pubkey]) {
+    if let Some(fee_payer) = signers.first() {
+        if !signers.contains(&trade.wallet) {
+            trade.wallet = *fee_payer;
+        }
+    }
+}
+
+// 按账户地址查找代币余额变化
+fn account_change<'a>(balances: &'a [TokenBalanceChange], account_keys: &[Pubkey], account: &Pubkey) -> Option<&'a TokenBalanceChange> {
+    let index = account_keys.iter().position(|k| k == account)?;
+    find_change(balances, index)
+}
+
+// 账户SOL余额变化的绝对值
+fn lamport_delta(meta: &TransactionStatusMeta, account_keys: &[Pubkey], account: &Pubkey) -> Option<u64> {
+    let index = account_keys.iter().position(|k| k == account)?;
+    let pre = *meta.pre_balances.get(index)?;
+    let post = *meta.post_balances.get(index)?;
+    Some(pre.abs_diff(post))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::{CompiledInstruction, MessageHeader};
+
+    fn trade(wallet: Pubkey) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet,
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: Pubkey::new_unique(),
+            output_token: Pubkey::new_unique(),
+            amount_in: 1,
+            amount_out: 1,
+            price: 1.0,
+            timestamp: 0,
+            token_decimals: None,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn raydium_deposit_is_classified_as_liquidity() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let raydium = raydium::RAYDIUM_AMM_V4_PROGRAM.parse::<Pubkey>().unwrap();
+        let account_keys = [keys.clone(), vec![raydium]].concat();
+        let mut data = vec![3u8];
+        data.extend_from_slice(&[0; 24]);
+        let tx = Transaction {
+            message: Some(Message {
+                account_keys: account_keys.iter().map(|key| key.to_bytes().to_vec()).collect(),
+                instructions: vec![CompiledInstruction { program_id_index: 3, accounts: vec![1, 2, 0], data }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let classified = TransactionParser::new().classify("sig", &tx, None, &keys[0]).unwrap();
+        let Some(TradeKind::Liquidity(event)) = classified else { panic!("expected liquidity, got {:?}", classified) };
+        assert_eq!((event.action, event.pool), (crate::types::LiquidityAction::Add, keys[2]));
+    }
+
+    #[test]
+    fn trades_are_attributed_to_a_signer() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let message = Message {
+            header: Some(MessageHeader { num_required_signatures: 2, ..Default::default() }),
+            ..Default::default()
+        };
+        let signers = signers(&message, &keys);
+        assert_eq!(signers, &keys[..2]);
+
+        // 第二个签名者自己的成交保持不变
+        let mut own = trade(keys[1]);
+        attribute_to_signer(&mut own, signers);
+        assert_eq!(own.wallet, keys[1]);
+
+        // 合约PDA作为用户账户时归到手续费支付者
+        let mut routed = trade(keys[3]);
+        attribute_to_signer(&mut routed, signers);
+        assert_eq!(routed.wallet, keys[0]);
     }
-}
\ No newline at end of file
+}
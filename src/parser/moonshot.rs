@@ -0,0 +1,173 @@
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+// Moonshot (DexLab) 程序地址
+pub const MOONSHOT_PROGRAM: &str = "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG";
+
+// Anchor指令鉴别器
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// buy/sell指令中的账户位置
+const SENDER_INDEX: usize = 0;
+const SENDER_TOKEN_ACCOUNT_INDEX: usize = 1;
+const CURVE_ACCOUNT_INDEX: usize = 2;
+const MINT_INDEX: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonshotSide {
+    Buy,
+    Sell,
+}
+
+// 指令参数中哪一侧是精确数量，另一侧只是滑点限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedSide {
+    ExactIn,
+    ExactOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct MoonshotTrade {
+    pub side: MoonshotSide,
+    pub fixed_side: FixedSide,
+    pub sender: Pubkey,
+    pub sender_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub curve_account: Pubkey,
+    pub token_amount: u64,
+    pub collateral_amount: u64,
+}
+
+impl MoonshotTrade {
+    // 代币数量为精确值时返回，否则为滑点限制，不代表实际成交
+    pub fn exact_token_amount(&self) -> Option<u64> {
+        match (self.side, self.fixed_side) {
+            (MoonshotSide::Buy, FixedSide::ExactOut) | (MoonshotSide::Sell, FixedSide::ExactIn) => Some(self.token_amount),
+            _ => None,
+        }
+    }
+
+    // SOL数量为精确值时返回，否则为滑点限制，不代表实际成交
+    pub fn exact_collateral_amount(&self) -> Option<u64> {
+        match (self.side, self.fixed_side) {
+            (MoonshotSide::Buy, FixedSide::ExactIn) | (MoonshotSide::Sell, FixedSide::ExactOut) => Some(self.collateral_amount),
+            _ => None,
+        }
+    }
+}
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(MOONSHOT_PROGRAM).unwrap()
+}
+
+// 曲线账户PDA: seeds = ["token", mint]
+pub fn derive_curve_account(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token", mint.as_ref()], &program_id()).0
+}
+
+// 只比较鉴别器，供预过滤使用
+pub fn is_trade_instruction(data: &[u8]) -> bool {
+    data.len() >= 8 && (data[..8] == BUY_DISCRIMINATOR || data[..8] == SELL_DISCRIMINATOR)
+}
+
+// 解析buy/sell指令
+// 数据布局: discriminator(8) + token_amount(u64) + collateral_amount(u64) + fixed_side(u8) + slippage_bps(u64)
+pub fn parse_trade_instruction(data: &[u8], accounts: &[Pubkey]) -> Option<MoonshotTrade> {
+    if data.len() < 8 + 8 + 8 + 1 + 8 {
+        return None;
+    }
+
+    let side = match &data[..8] {
+        d if d == BUY_DISCRIMINATOR => MoonshotSide::Buy,
+        d if d == SELL_DISCRIMINATOR => MoonshotSide::Sell,
+        _ => return None,
+    };
+
+    let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let token_amount = read_u64(8);
+    let collateral_amount = read_u64(16);
+    let fixed_side = match data[24] {
+        0 => FixedSide::ExactIn,
+        1 => FixedSide::ExactOut,
+        _ => return None,
+    };
+
+    let sender = *accounts.get(SENDER_INDEX)?;
+    let sender_token_account = *accounts.get(SENDER_TOKEN_ACCOUNT_INDEX)?;
+    let curve_account = *accounts.get(CURVE_ACCOUNT_INDEX)?;
+    let mint = *accounts.get(MINT_INDEX)?;
+
+    Some(MoonshotTrade {
+        side,
+        fixed_side,
+        sender,
+        sender_token_account,
+        mint,
+        curve_account,
+        token_amount,
+        collateral_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade_data(discriminator: [u8; 8], token_amount: u64, collateral_amount: u64, fixed_side: u8) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&token_amount.to_le_bytes());
+        data.extend_from_slice(&collateral_amount.to_le_bytes());
+        data.push(fixed_side);
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data
+    }
+
+    fn trade_accounts(mint: Pubkey) -> Vec<Pubkey> {
+        let mut accounts: Vec<Pubkey> = (0..11).map(|_| Pubkey::new_unique()).collect();
+        accounts[CURVE_ACCOUNT_INDEX] = derive_curve_account(&mint);
+        accounts[MINT_INDEX] = mint;
+        accounts
+    }
+
+    #[test]
+    fn parses_buy_with_exact_collateral() {
+        // 以0.5 SOL买入，最少获得1_234_567_890_000个代币单位
+        let data = trade_data(BUY_DISCRIMINATOR, 1_234_567_890_000, 500_000_000, 0);
+        let mint = Pubkey::new_unique();
+        let accounts = trade_accounts(mint);
+
+        let trade = parse_trade_instruction(&data, &accounts).unwrap();
+        assert_eq!(trade.side, MoonshotSide::Buy);
+        assert_eq!(trade.fixed_side, FixedSide::ExactIn);
+        assert_eq!(trade.sender, accounts[SENDER_INDEX]);
+        assert_eq!(trade.sender_token_account, accounts[SENDER_TOKEN_ACCOUNT_INDEX]);
+        assert_eq!(trade.mint, mint);
+        assert_eq!(trade.curve_account, derive_curve_account(&mint));
+        assert_eq!(trade.exact_collateral_amount(), Some(500_000_000));
+        assert_eq!(trade.exact_token_amount(), None);
+    }
+
+    #[test]
+    fn parses_sell_with_exact_tokens() {
+        let data = trade_data(SELL_DISCRIMINATOR, 2_000_000_000_000, 1_000_000, 0);
+        let accounts = trade_accounts(Pubkey::new_unique());
+
+        let trade = parse_trade_instruction(&data, &accounts).unwrap();
+        assert_eq!(trade.side, MoonshotSide::Sell);
+        assert_eq!(trade.exact_token_amount(), Some(2_000_000_000_000));
+        assert_eq!(trade.exact_collateral_amount(), None);
+    }
+
+    #[test]
+    fn rejects_unknown_discriminator() {
+        let data = trade_data([0; 8], 1, 1, 0);
+        assert!(parse_trade_instruction(&data, &trade_accounts(Pubkey::new_unique())).is_none());
+    }
+
+    #[test]
+    fn rejects_short_data() {
+        let data = trade_data(BUY_DISCRIMINATOR, 1, 1, 0);
+        assert!(parse_trade_instruction(&data[..data.len() - 1], &trade_accounts(Pubkey::new_unique())).is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use solana_sdk::pubkey::Pubkey;
+
+// Phoenix v1 订单簿程序地址
+pub const PHOENIX_PROGRAM: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR4dCKn2PaAE";
+
+// 指令标签(首字节)，Swap为立即成交的吃单
+const SWAP_TAG: u8 = 0;
+
+// Swap指令中的账户位置
+const TRADER_INDEX: usize = 3;
+const BASE_ACCOUNT_INDEX: usize = 4;
+const QUOTE_ACCOUNT_INDEX: usize = 5;
+
+// 订单参数只包含限价和数量上限，买卖方向和实际成交都取自余额变化
+#[derive(Debug, Clone)]
+pub struct PhoenixSwap {
+    pub trader: Pubkey,
+    pub base_account: Pubkey,
+    pub quote_account: Pubkey,
+}
+
+// 只比较指令标签，供预过滤使用
+pub fn is_swap_instruction(data: &[u8]) -> bool {
+    data.first() == Some(&SWAP_TAG)
+}
+
+pub fn parse_swap_instruction(data: &[u8], accounts: &[Pubkey]) -> Option<PhoenixSwap> {
+    if data.first() != Some(&SWAP_TAG) {
+        return None;
+    }
+
+    Some(PhoenixSwap {
+        trader: *accounts.get(TRADER_INDEX)?,
+        base_account: *accounts.get(BASE_ACCOUNT_INDEX)?,
+        quote_account: *accounts.get(QUOTE_ACCOUNT_INDEX)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_swap_accounts_and_ignores_other_instructions() {
+        let accounts: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+
+        let swap = parse_swap_instruction(&[SWAP_TAG, 1, 2, 3], &accounts).unwrap();
+        assert_eq!(swap.trader, accounts[3]);
+        assert_eq!(swap.base_account, accounts[4]);
+        assert_eq!(swap.quote_account, accounts[5]);
+
+        // PlaceLimitOrder挂单不是成交
+        assert!(parse_swap_instruction(&[2, 1, 2, 3], &accounts).is_none());
+        assert!(parse_swap_instruction(&[SWAP_TAG], &accounts[..5]).is_none());
+    }
+}
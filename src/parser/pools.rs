@@ -0,0 +1,133 @@
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use crate::types::DexType;
+
+// Pump.fun create指令 sha256("global:create")[..8]
+const PUMP_CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+// Raydium AMM v4 initialize2指令标签
+const RAYDIUM_INITIALIZE2: u8 = 1;
+
+// create指令账户: mint(0) ... bonding_curve(2) ... user(7)
+const PUMP_MINT_INDEX: usize = 0;
+const PUMP_BONDING_CURVE_INDEX: usize = 2;
+const PUMP_CREATOR_INDEX: usize = 7;
+
+// Pump.fun毕业迁移时创建Raydium池子的账户
+pub const PUMP_MIGRATION_AUTHORITY: Pubkey = solana_sdk::pubkey!("39azUYFWPz3VHgKCf3VChUwbpURdCHRxjWVowf5jUJjg");
+
+// initialize2指令账户: amm(4) ... coin_mint(8) pc_mint(9) ... user_wallet(17)
+const RAYDIUM_AMM_INDEX: usize = 4;
+const RAYDIUM_COIN_MINT_INDEX: usize = 8;
+const RAYDIUM_PC_MINT_INDEX: usize = 9;
+const RAYDIUM_CREATOR_INDEX: usize = 17;
+
+// 新创建的池子/代币
+#[derive(Debug, Clone, Serialize)]
+pub struct NewPool {
+    pub signature: String,
+    pub dex: DexType,
+    pub pool: Pubkey,
+    pub base_mint: Pubkey,
+    // Pump.fun的bonding curve以SOL计价，没有单独的计价代币账户
+    pub quote_mint: Pubkey,
+    pub creator: Pubkey,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl NewPool {
+    // Pump.fun代币毕业后迁移到的Raydium池子，返回迁移的代币
+    pub fn pump_migration(&self) -> Option<Pubkey> {
+        if self.dex != DexType::Raydium || self.creator != PUMP_MIGRATION_AUTHORITY {
+            return None;
+        }
+        let wsol = spl_token::native_mint::id();
+        Some(if self.base_mint == wsol { self.quote_mint } else { self.base_mint })
+    }
+}
+
+#[derive(BorshDeserialize)]
+struct PumpCreateArgs {
+    name: String,
+    symbol: String,
+}
+
+pub fn parse_new_pool(signature: &str, dex: DexType, data: &[u8], accounts: &[Pubkey]) -> Option<NewPool> {
+    match dex {
+        DexType::PumpFun => {
+            let mut args = data.strip_prefix(&PUMP_CREATE_DISCRIMINATOR)?;
+            let args = PumpCreateArgs::deserialize(&mut args).ok()?;
+            Some(NewPool {
+                signature: signature.to_string(),
+                dex,
+                pool: *accounts.get(PUMP_BONDING_CURVE_INDEX)?,
+                base_mint: *accounts.get(PUMP_MINT_INDEX)?,
+                quote_mint: spl_token::native_mint::id(),
+                creator: *accounts.get(PUMP_CREATOR_INDEX)?,
+                name: Some(args.name),
+                symbol: Some(args.symbol),
+            })
+        }
+        DexType::Raydium => {
+            if data.first() != Some(&RAYDIUM_INITIALIZE2) {
+                return None;
+            }
+            Some(NewPool {
+                signature: signature.to_string(),
+                dex,
+                pool: *accounts.get(RAYDIUM_AMM_INDEX)?,
+                base_mint: *accounts.get(RAYDIUM_COIN_MINT_INDEX)?,
+                quote_mint: *accounts.get(RAYDIUM_PC_MINT_INDEX)?,
+                creator: *accounts.get(RAYDIUM_CREATOR_INDEX)?,
+                name: None,
+                symbol: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn parses_pump_create() {
+        assert_eq!(Sha256::digest(b"global:create")[..8], PUMP_CREATE_DISCRIMINATOR);
+
+        let accounts: Vec<Pubkey> = (0..14).map(|_| Pubkey::new_unique()).collect();
+        let mut data = PUMP_CREATE_DISCRIMINATOR.to_vec();
+        let args = ("Dog Coin".to_string(), "DOG".to_string(), "https://example.com/dog.json".to_string());
+        BorshSerialize::serialize(&args, &mut data).unwrap();
+
+        let pool = parse_new_pool("sig", DexType::PumpFun, &data, &accounts).unwrap();
+        assert_eq!((pool.base_mint, pool.pool, pool.creator), (accounts[0], accounts[2], accounts[7]));
+        assert_eq!(pool.symbol.as_deref(), Some("DOG"));
+    }
+
+    #[test]
+    fn parses_raydium_initialize2_and_ignores_swaps() {
+        let accounts: Vec<Pubkey> = (0..21).map(|_| Pubkey::new_unique()).collect();
+        let mut data = vec![RAYDIUM_INITIALIZE2, 254];
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&2_000u64.to_le_bytes());
+
+        let pool = parse_new_pool("sig", DexType::Raydium, &data, &accounts).unwrap();
+        assert_eq!((pool.pool, pool.base_mint, pool.quote_mint, pool.creator), (accounts[4], accounts[8], accounts[9], accounts[17]));
+
+        assert_eq!(pool.pump_migration(), None);
+
+        let mut migration_accounts = accounts.clone();
+        migration_accounts[17] = PUMP_MIGRATION_AUTHORITY;
+        migration_accounts[9] = spl_token::native_mint::id();
+        let migration = parse_new_pool("sig", DexType::Raydium, &data, &migration_accounts).unwrap();
+        assert_eq!(migration.pump_migration(), Some(accounts[8]));
+
+        data[0] = 9;
+        assert!(parse_new_pool("sig", DexType::Raydium, &data, &accounts).is_none());
+    }
+}
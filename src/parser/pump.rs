@@ -0,0 +1,125 @@
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+use super::events;
+
+// Pump.fun 程序地址
+pub const PUMP_PROGRAM: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwdFi";
+
+// Pump.fun代币固定为6位小数
+pub const PUMP_TOKEN_DECIMALS: u8 = 6;
+
+// Anchor指令鉴别器
+pub(crate) const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// Anchor事件鉴别器 sha256("event:TradeEvent")[..8]
+const TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+
+// TradeEvent中的精确成交量，比余额差推断可靠；之后的储备量等字段不需要
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct TradeEvent {
+    pub mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
+
+pub fn is_trade_instruction(data: &[u8]) -> bool {
+    data.starts_with(&BUY_DISCRIMINATOR)
+        || data.starts_with(&SELL_DISCRIMINATOR)
+        || parse_cpi_event(data).is_some()
+}
+
+// buy返回Some(true)，sell返回Some(false)
+pub fn is_buy_instruction(data: &[u8]) -> Option<bool> {
+    if data.starts_with(&BUY_DISCRIMINATOR) {
+        Some(true)
+    } else if data.starts_with(&SELL_DISCRIMINATOR) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// 新版程序通过emit_cpi!把事件作为内部自调用指令发出
+pub fn parse_cpi_event(data: &[u8]) -> Option<TradeEvent> {
+    events::decode_event(events::cpi_event_data(data)?, &TRADE_EVENT_DISCRIMINATOR)
+}
+
+// 旧版程序把事件写在"Program data:"日志里
+pub fn parse_log_event(data: &[u8]) -> Option<TradeEvent> {
+    events::decode_event(data, &TRADE_EVENT_DISCRIMINATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use borsh::BorshSerialize;
+    use sha2::{Digest, Sha256};
+    use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+
+    #[derive(BorshSerialize)]
+    struct TradeEventV2 {
+        mint: Pubkey,
+        sol_amount: u64,
+        token_amount: u64,
+        is_buy: bool,
+        user: Pubkey,
+        timestamp: i64,
+        virtual_sol_reserves: u64,
+        virtual_token_reserves: u64,
+        // 新版本追加的字段
+        real_sol_reserves: u64,
+    }
+
+    fn event_bytes(mint: Pubkey, user: Pubkey) -> Vec<u8> {
+        let mut data = TRADE_EVENT_DISCRIMINATOR.to_vec();
+        TradeEventV2 {
+            mint,
+            sol_amount: 1_500_000_000,
+            token_amount: 42_000_000,
+            is_buy: true,
+            user,
+            timestamp: 1_700_000_000,
+            virtual_sol_reserves: 30,
+            virtual_token_reserves: 1_000,
+            real_sol_reserves: 5,
+        }
+        .serialize(&mut data)
+        .unwrap();
+        data
+    }
+
+    #[test]
+    fn discriminators_match_anchor_hashes() {
+        let hash = |s: &str| Sha256::digest(s.as_bytes())[..8].to_vec();
+        assert_eq!(hash("global:buy"), BUY_DISCRIMINATOR);
+        assert_eq!(hash("global:sell"), SELL_DISCRIMINATOR);
+        assert_eq!(hash("event:TradeEvent"), TRADE_EVENT_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn decodes_trade_event_from_logs_and_cpi() {
+        let (mint, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = event_bytes(mint, user);
+
+        let meta = TransactionStatusMeta {
+            log_messages: vec![
+                format!("Program {} invoke [1]", PUMP_PROGRAM),
+                "Program log: Instruction: Buy".to_string(),
+                format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(&data)),
+            ],
+            ..Default::default()
+        };
+        let event = events::program_data(&meta).find_map(|d| parse_log_event(&d)).unwrap();
+        assert_eq!((event.mint, event.user), (mint, user));
+        assert_eq!((event.sol_amount, event.token_amount, event.is_buy), (1_500_000_000, 42_000_000, true));
+
+        let cpi_data = [events::EVENT_IX_TAG.to_vec(), data].concat();
+        assert!(is_trade_instruction(&cpi_data));
+        assert_eq!(parse_cpi_event(&cpi_data).unwrap().timestamp, 1_700_000_000);
+    }
+}
@@ -0,0 +1,72 @@
+use solana_sdk::pubkey::Pubkey;
+use crate::types::LiquidityAction;
+
+// Raydium AMM v4 程序地址
+pub const RAYDIUM_AMM_V4_PROGRAM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+// 指令标签(非Anchor程序，首字节即指令类型)
+const SWAP_BASE_IN: u8 = 9;
+const SWAP_BASE_OUT: u8 = 11;
+const DEPOSIT: u8 = 3;
+const WITHDRAW: u8 = 4;
+
+// deposit/withdraw指令中AMM账户的位置
+pub const LIQUIDITY_AMM_INDEX: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct RaydiumSwap {
+    pub user: Pubkey,
+    pub source_account: Pubkey,
+    pub destination_account: Pubkey,
+}
+
+pub fn is_swap_instruction(data: &[u8]) -> bool {
+    matches!(data.first(), Some(&SWAP_BASE_IN) | Some(&SWAP_BASE_OUT))
+}
+
+// deposit: tag(1) + max_coin(u64) + max_pc(u64) + base_side(u64)；withdraw: tag(1) + amount(u64)
+pub fn liquidity_action(data: &[u8]) -> Option<LiquidityAction> {
+    match *data.first()? {
+        DEPOSIT if data.len() > 8 * 3 => Some(LiquidityAction::Add),
+        WITHDRAW if data.len() > 8 => Some(LiquidityAction::Remove),
+        _ => None,
+    }
+}
+
+// 数据布局: tag(1) + amount(u64) + limit(u64)
+// 账户数量随是否带target_orders变化(17或18)，用户的三个账户固定在最后
+pub fn parse_swap_instruction(data: &[u8], accounts: &[Pubkey]) -> Option<RaydiumSwap> {
+    if data.len() < 1 + 8 + 8 || !is_swap_instruction(data) || accounts.len() < 17 {
+        return None;
+    }
+
+    let n = accounts.len();
+    Some(RaydiumSwap {
+        user: accounts[n - 1],
+        source_account: accounts[n - 3],
+        destination_account: accounts[n - 2],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_accounts_are_taken_from_the_end() {
+        let mut data = vec![SWAP_BASE_IN];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&990u64.to_le_bytes());
+
+        for count in [17, 18] {
+            let accounts: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+            let swap = parse_swap_instruction(&data, &accounts).unwrap();
+            assert_eq!(swap.user, accounts[count - 1]);
+            assert_eq!(swap.source_account, accounts[count - 3]);
+            assert_eq!(swap.destination_account, accounts[count - 2]);
+        }
+
+        let accounts: Vec<Pubkey> = (0..16).map(|_| Pubkey::new_unique()).collect();
+        assert!(parse_swap_instruction(&data, &accounts).is_none());
+    }
+}
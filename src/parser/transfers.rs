@@ -0,0 +1,146 @@
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{Message, TransactionStatusMeta};
+use crate::token_program::TOKEN_2022_PROGRAM_ID;
+use crate::types::TransferEvent;
+use super::balances::TokenBalanceChange;
+
+const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+// 只允许转账相关的顶层程序，出现其他程序(DEX、路由器)时不视为转账
+fn is_transfer_program(program: &Pubkey) -> bool {
+    *program == solana_sdk::system_program::id()
+        || *program == solana_sdk::compute_budget::id()
+        || *program == spl_token::id()
+        || *program == TOKEN_2022_PROGRAM_ID
+        || *program == spl_associated_token_account::id()
+        || program.to_string() == MEMO_PROGRAM
+}
+
+// 钱包收到或转出单一代币(或SOL)的普通转账、空投
+// 代币以余额变化为准，对方取同一代币反向变化的所有者；没有代币变化时按原生SOL变化判断
+pub fn classify_transfer(
+    signature: &str,
+    wallet: &Pubkey,
+    account_keys: &[Pubkey],
+    message: &Message,
+    meta: &TransactionStatusMeta,
+    balances: &[TokenBalanceChange],
+) -> Option<TransferEvent> {
+    let only_transfers = message.instructions.iter()
+        .all(|ix| account_keys.get(ix.program_id_index as usize).is_some_and(is_transfer_program));
+    if message.instructions.is_empty() || !only_transfers {
+        return None;
+    }
+
+    let wallet_str = wallet.to_string();
+    let changed: Vec<_> = balances.iter()
+        .filter(|c| c.owner == wallet_str && c.delta() != 0)
+        .collect();
+    let (mint, delta, counterparty, new_counterparty) = match changed.as_slice() {
+        [change] => {
+            let counterparty = balances.iter()
+                .find(|c| c.mint == change.mint && c.owner != wallet_str && c.delta().signum() == -change.delta().signum())
+                .and_then(|c| c.owner.parse().ok());
+            (change.mint.parse().ok()?, change.delta(), counterparty, false)
+        }
+        [] => {
+            let index = account_keys.iter().position(|k| k == wallet)?;
+            let mut delta = *meta.post_balances.get(index)? as i128 - *meta.pre_balances.get(index)? as i128;
+            if index == 0 {
+                delta += meta.fee as i128;
+            }
+            let counterparty = account_keys.iter().enumerate()
+                .filter(|(i, _)| *i != index)
+                .find(|(i, _)| {
+                    let other = meta.post_balances.get(*i).zip(meta.pre_balances.get(*i))
+                        .map_or(0, |(post, pre)| *post as i128 - *pre as i128);
+                    other.signum() == -delta.signum()
+                })
+                .map(|(i, key)| (i, *key));
+            let new_counterparty = counterparty.is_some_and(|(i, _)| meta.pre_balances.get(i) == Some(&0));
+            (spl_token::native_mint::id(), delta, counterparty.map(|(_, key)| key), new_counterparty)
+        }
+        _ => return None,
+    };
+    if delta == 0 {
+        return None;
+    }
+
+    let incoming = delta > 0;
+    let (from, to) = if incoming { (counterparty, Some(*wallet)) } else { (Some(*wallet), counterparty) };
+    Some(TransferEvent {
+        signature: signature.to_string(),
+        wallet: *wallet,
+        mint,
+        from,
+        to,
+        amount: delta.unsigned_abs() as u64,
+        incoming,
+        new_counterparty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::balances::token_balance_changes;
+    use yellowstone_grpc_proto::prelude::{CompiledInstruction, TokenBalance, UiTokenAmount};
+
+    fn balance(index: u32, mint: &Pubkey, owner: &Pubkey, amount: u64) -> TokenBalance {
+        TokenBalance {
+            account_index: index,
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            ui_token_amount: Some(UiTokenAmount { amount: amount.to_string(), decimals: 6, ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn token_airdrop_is_an_incoming_transfer() {
+        let (sender, wallet, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = vec![sender, Pubkey::new_unique(), Pubkey::new_unique(), spl_token::id()];
+        let message = Message {
+            instructions: vec![CompiledInstruction { program_id_index: 3, accounts: vec![1, 2, 0], data: vec![3] }],
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            pre_balances: vec![10, 0, 0, 0],
+            post_balances: vec![5, 0, 0, 0],
+            fee: 5,
+            pre_token_balances: vec![balance(1, &mint, &sender, 1_000), balance(2, &mint, &wallet, 0)],
+            post_token_balances: vec![balance(1, &mint, &sender, 400), balance(2, &mint, &wallet, 600)],
+            ..Default::default()
+        };
+
+        let transfer = classify_transfer("sig", &wallet, &keys, &message, &meta, &token_balance_changes(&meta)).unwrap();
+        assert!(transfer.incoming);
+        assert_eq!((transfer.mint, transfer.amount), (mint, 600));
+        assert_eq!((transfer.from, transfer.to), (Some(sender), Some(wallet)));
+
+        // 出现其他程序时不是转账
+        let mut swap = message.clone();
+        swap.instructions[0].program_id_index = 2;
+        assert!(classify_transfer("sig", &wallet, &keys, &swap, &meta, &token_balance_changes(&meta)).is_none());
+    }
+
+    #[test]
+    fn sol_transfer_to_an_empty_address_is_flagged_new() {
+        let (wallet, fresh) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = vec![wallet, fresh, solana_sdk::system_program::id()];
+        let message = Message {
+            instructions: vec![CompiledInstruction { program_id_index: 2, accounts: vec![0, 1], data: vec![2] }],
+            ..Default::default()
+        };
+        let meta = TransactionStatusMeta {
+            pre_balances: vec![10_000_000_005, 0, 1],
+            post_balances: vec![5_000_000_000, 5_000_000_000, 1],
+            fee: 5,
+            ..Default::default()
+        };
+
+        let transfer = classify_transfer("sig", &wallet, &keys, &message, &meta, &token_balance_changes(&meta)).unwrap();
+        assert!(!transfer.incoming && transfer.new_counterparty);
+        assert_eq!((transfer.to, transfer.amount), (Some(fresh), 5_000_000_000));
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use crate::types::WSOL_MINT;
+
+const JUPITER_PRICE_API: &str = "https://api.jup.ag/price/v2";
+const DEXSCREENER_TOKEN_API: &str = "https://api.dexscreener.com/latest/dex/tokens";
+
+#[derive(Debug, Clone, Copy)]
+pub struct TokenPrice {
+    pub price_usd: f64,
+    // 以SOL计价的价格，部分数据源不提供
+    pub price_sol: Option<f64>,
+}
+
+// 价格数据源
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn fetch_price<'a>(&'a self, mint: &'a str) -> BoxFuture<'a, Result<TokenPrice>>;
+}
+
+// Jupiter Price API v2
+pub struct JupiterPriceProvider {
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct JupiterPriceResponse {
+    data: HashMap<String, Option<JupiterPriceEntry>>,
+}
+
+#[derive(Deserialize)]
+struct JupiterPriceEntry {
+    price: String,
+}
+
+impl JupiterPriceProvider {
+    pub fn new(client: reqwest::Client) -> Self {
+        JupiterPriceProvider { client }
+    }
+}
+
+impl PriceProvider for JupiterPriceProvider {
+    fn name(&self) -> &'static str {
+        "Jupiter"
+    }
+
+    fn fetch_price<'a>(&'a self, mint: &'a str) -> BoxFuture<'a, Result<TokenPrice>> {
+        Box::pin(async move {
+            let response: JupiterPriceResponse = self.client
+                .get(JUPITER_PRICE_API)
+                .query(&[("ids", mint)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let entry = response.data.get(mint)
+                .and_then(|e| e.as_ref())
+                .with_context(|| format!("Jupiter has no price for {}", mint))?;
+
+            Ok(TokenPrice {
+                price_usd: entry.price.parse()?,
+                price_sol: None,
+            })
+        })
+    }
+}
+
+// DexScreener，取流动性最高的交易对
+pub struct DexScreenerPriceProvider {
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct DexScreenerResponse {
+    pairs: Option<Vec<DexScreenerPair>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DexScreenerPair {
+    chain_id: String,
+    base_token: DexScreenerToken,
+    quote_token: DexScreenerToken,
+    price_usd: Option<String>,
+    price_native: String,
+    liquidity: Option<DexScreenerLiquidity>,
+}
+
+#[derive(Deserialize)]
+struct DexScreenerToken {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct DexScreenerLiquidity {
+    usd: Option<f64>,
+}
+
+impl DexScreenerPriceProvider {
+    pub fn new(client: reqwest::Client) -> Self {
+        DexScreenerPriceProvider { client }
+    }
+}
+
+impl PriceProvider for DexScreenerPriceProvider {
+    fn name(&self) -> &'static str {
+        "DexScreener"
+    }
+
+    fn fetch_price<'a>(&'a self, mint: &'a str) -> BoxFuture<'a, Result<TokenPrice>> {
+        Box::pin(async move {
+            let response: DexScreenerResponse = self.client
+                .get(format!("{}/{}", DEXSCREENER_TOKEN_API, mint))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let pair = response.pairs.unwrap_or_default()
+                .into_iter()
+                .filter(|p| p.chain_id == "solana" && p.base_token.address == mint && p.price_usd.is_some())
+                .max_by(|a, b| {
+                    let liq = |p: &DexScreenerPair| p.liquidity.as_ref().and_then(|l| l.usd).unwrap_or(0.0);
+                    liq(a).total_cmp(&liq(b))
+                })
+                .with_context(|| format!("DexScreener has no Solana pair for {}", mint))?;
+
+            let price_sol = if pair.quote_token.address == WSOL_MINT {
+                pair.price_native.parse().ok()
+            } else {
+                None
+            };
+
+            Ok(TokenPrice {
+                price_usd: pair.price_usd.unwrap_or_default().parse()?,
+                price_sol,
+            })
+        })
+    }
+}
+
+// 带缓存和限速的价格预言机，按顺序尝试各数据源
+pub struct PriceOracle {
+    providers: Vec<Box<dyn PriceProvider>>,
+    cache: Mutex<HashMap<String, (Instant, TokenPrice)>>,
+    cache_ttl: Duration,
+    min_request_interval: Duration,
+    last_request: tokio::sync::Mutex<Option<Instant>>,
+    refreshing: Mutex<HashSet<String>>,
+}
+
+impl PriceOracle {
+    pub fn new(providers: Vec<Box<dyn PriceProvider>>, cache_ttl: Duration, min_request_interval: Duration) -> Self {
+        PriceOracle {
+            providers,
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl,
+            min_request_interval,
+            last_request: tokio::sync::Mutex::new(None),
+            refreshing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    // 默认配置: Jupiter优先，DexScreener兜底
+    pub fn with_default_providers() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+
+        PriceOracle::new(
+            vec![
+                Box::new(JupiterPriceProvider::new(client.clone())),
+                Box::new(DexScreenerPriceProvider::new(client)),
+            ],
+            Duration::from_secs(30),
+            Duration::from_millis(200),
+        )
+    }
+
+    pub async fn get_price(&self, mint: &str) -> Option<TokenPrice> {
+        if let Some(price) = self.cached(mint) {
+            return Some(price);
+        }
+
+        for provider in &self.providers {
+            self.wait_for_rate_limit().await;
+
+            match provider.fetch_price(mint).await {
+                Ok(mut price) => {
+                    if price.price_sol.is_none() && mint != WSOL_MINT {
+                        price.price_sol = self.cached(WSOL_MINT)
+                            .filter(|sol| sol.price_usd > 0.0)
+                            .map(|sol| price.price_usd / sol.price_usd);
+                    }
+                    self.store(mint, price);
+                    return Some(price);
+                }
+                Err(e) => warn!("{} price lookup failed for {}: {:?}", provider.name(), mint, e),
+            }
+        }
+
+        None
+    }
+
+    pub async fn sol_usd(&self) -> Option<f64> {
+        self.get_price(WSOL_MINT).await.map(|p| p.price_usd)
+    }
+
+    // 只读缓存，不发起网络请求；用于交易处理热路径
+    pub fn cached_price(&self, mint: &str) -> Option<TokenPrice> {
+        self.cached(mint)
+    }
+
+    // 在后台任务中刷新价格，同一代币同时只有一个刷新任务
+    pub fn refresh_in_background(self: &Arc<Self>, mint: &str) {
+        {
+            let mut refreshing = self.refreshing.lock().unwrap_or_else(|e| e.into_inner());
+            if !refreshing.insert(mint.to_string()) {
+                return;
+            }
+        }
+
+        let oracle = Arc::clone(self);
+        let mint = mint.to_string();
+        tokio::spawn(async move {
+            oracle.get_price(&mint).await;
+            oracle.refreshing.lock().unwrap_or_else(|e| e.into_inner()).remove(&mint);
+        });
+    }
+
+    fn cached(&self, mint: &str) -> Option<TokenPrice> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(mint)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, price)| *price)
+    }
+
+    fn store(&self, mint: &str, price: TokenPrice) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(mint.to_string(), (Instant::now(), price));
+    }
+
+    async fn wait_for_rate_limit(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_request_interval {
+                tokio::time::sleep(self.min_request_interval - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+// 内置profile，mirror即trading_settings本身
+pub const BUILTIN: [&str; 3] = ["mirror", "sniper", "conservative"];
+
+// 跟单的交易参数：仓位、滑点、风控和手续费
+// 仅供展示：在有执行器之前，这些参数(包括max_open_positions和stop_loss_pct)不会被读取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingSettings {
+    // 单笔跟单的最大SOL数量
+    pub max_position_size: f64,
+    pub slippage_tolerance: f64,
+    pub gas_price_multiplier: f64,
+    // 同时持有的代币数上限
+    #[serde(default = "default_max_open_positions")]
+    pub max_open_positions: usize,
+    // 持仓亏损达到该百分比时卖出，None为不止损
+    #[serde(default)]
+    pub stop_loss_pct: Option<f64>,
+}
+
+fn default_max_open_positions() -> usize {
+    10
+}
+
+// 对交易参数的单项覆盖，未设置的字段沿用下层
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsOverrides {
+    #[serde(default)]
+    pub max_position_size: Option<f64>,
+    #[serde(default)]
+    pub slippage_tolerance: Option<f64>,
+    #[serde(default)]
+    pub gas_price_multiplier: Option<f64>,
+    #[serde(default)]
+    pub max_open_positions: Option<usize>,
+    #[serde(default)]
+    pub stop_loss_pct: Option<f64>,
+}
+
+impl SettingsOverrides {
+    pub fn apply(&self, base: &TradingSettings) -> TradingSettings {
+        TradingSettings {
+            max_position_size: self.max_position_size.unwrap_or(base.max_position_size),
+            slippage_tolerance: self.slippage_tolerance.unwrap_or(base.slippage_tolerance),
+            gas_price_multiplier: self.gas_price_multiplier.unwrap_or(base.gas_price_multiplier),
+            max_open_positions: self.max_open_positions.unwrap_or(base.max_open_positions),
+            stop_loss_pct: self.stop_loss_pct.or(base.stop_loss_pct),
+        }
+    }
+}
+
+// 目标钱包选用的profile，其余字段为单项覆盖，如 {"profile": "sniper", "max_position_size": 0.02}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletProfile {
+    pub profile: String,
+    #[serde(flatten)]
+    pub overrides: SettingsOverrides,
+}
+
+pub fn builtin(name: &str) -> Option<SettingsOverrides> {
+    match name {
+        "mirror" => Some(SettingsOverrides::default()),
+        // 小仓位、高滑点和优先费，抢在早期成交
+        "sniper" => Some(SettingsOverrides {
+            max_position_size: Some(0.05),
+            slippage_tolerance: Some(0.25),
+            gas_price_multiplier: Some(3.0),
+            max_open_positions: Some(20),
+            stop_loss_pct: Some(30.0),
+        }),
+        // 低滑点、少持仓、严格止损
+        "conservative" => Some(SettingsOverrides {
+            max_position_size: Some(0.02),
+            slippage_tolerance: Some(0.02),
+            gas_price_multiplier: Some(1.0),
+            max_open_positions: Some(3),
+            stop_loss_pct: Some(10.0),
+        }),
+        _ => None,
+    }
+}
+
+// 合并顺序: trading_settings -> profile -> 钱包的单项覆盖；未选择profile时为mirror
+pub fn resolve(base: &TradingSettings, selection: Option<&WalletProfile>) -> Result<(String, TradingSettings)> {
+    let Some(selection) = selection else { return Ok(("mirror".to_string(), base.clone())) };
+    let preset = builtin(&selection.profile)
+        .with_context(|| format!("Unknown profile {} (available: {})", selection.profile, BUILTIN.join(", ")))?;
+    Ok((selection.profile.clone(), selection.overrides.apply(&preset.apply(base))))
+}
+
+pub fn print_settings(label: &str, settings: &TradingSettings) {
+    println!("{}", label);
+    println!("  max_position_size:    {} SOL", settings.max_position_size);
+    println!("  slippage_tolerance:   {:.2}%", settings.slippage_tolerance * 100.0);
+    println!("  gas_price_multiplier: {}", settings.gas_price_multiplier);
+    println!("  max_open_positions:   {}", settings.max_open_positions);
+    println!("  stop_loss_pct:        {}", settings.stop_loss_pct.map(|pct| format!("{}%", pct)).unwrap_or_else(|| "none".to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallet_overrides_apply_on_top_of_the_profile() {
+        let base = TradingSettings {
+            max_position_size: 0.1,
+            slippage_tolerance: 0.05,
+            gas_price_multiplier: 1.2,
+            max_open_positions: 10,
+            stop_loss_pct: None,
+        };
+        let selection: WalletProfile = serde_json::from_str(r#"{"profile": "sniper", "max_position_size": 0.01}"#).unwrap();
+
+        let (name, settings) = resolve(&base, Some(&selection)).unwrap();
+        assert_eq!(name, "sniper");
+        assert_eq!((settings.max_position_size, settings.slippage_tolerance), (0.01, 0.25));
+        assert_eq!(settings.stop_loss_pct, Some(30.0));
+
+        assert_eq!(resolve(&base, None).unwrap().1, base);
+        let unknown = WalletProfile { profile: "yolo".to_string(), overrides: SettingsOverrides::default() };
+        assert!(resolve(&base, Some(&unknown)).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::warn;
+use crate::annotations::AnnotationStore;
+use crate::types::TradeDetails;
+
+const ACTIVE_SUFFIX: &str = ".jsonl";
+const ARCHIVE_SUFFIX: &str = ".jsonl.gz";
+
+// 交易记录配置，每天一个文件，超过大小上限时提前轮转
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecorderConfig {
+    #[serde(default = "default_dir")]
+    pub dir: String,
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    // 待写入队列长度，写入跟不上时新记录被丢弃而不是阻塞监控
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+}
+
+fn default_dir() -> String {
+    "trades".to_string()
+}
+
+fn default_max_file_bytes() -> u64 {
+    50 * 1024 * 1024
+}
+
+fn default_queue_capacity() -> usize {
+    10_000
+}
+
+// 单次批量写入的最大条数
+const MAX_BATCH: usize = 256;
+
+// 读取记录时的筛选条件
+#[derive(Debug, Clone, Default)]
+pub struct RecordFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub wallet: Option<Pubkey>,
+    pub mint: Option<Pubkey>,
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl RecordFilter {
+    pub fn matches(&self, trade: &TradeDetails) -> bool {
+        self.since.is_none_or(|since| trade.timestamp >= since)
+            && self.until.is_none_or(|until| trade.timestamp < until)
+            && self.wallet.is_none_or(|wallet| trade.wallet == wallet)
+            && self.mint.is_none_or(|mint| trade.input_token == mint || trade.output_token == mint)
+            && self.tag.as_ref().is_none_or(|tag| trade.annotation.as_ref().is_some_and(|a| a.tags.contains(tag)))
+    }
+}
+
+impl RecordFilter {
+    // 命令行参数: --since/--until <unix秒> --wallet <地址> --mint <地址> --tag <标签> --limit <N>
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut filter = RecordFilter::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().with_context(|| format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--since" => filter.since = Some(value.parse()?),
+                "--until" => filter.until = Some(value.parse()?),
+                "--wallet" => filter.wallet = Some(value.parse()?),
+                "--mint" => filter.mint = Some(value.parse()?),
+                "--tag" => filter.tag = Some(value.clone()),
+                "--limit" => filter.limit = Some(value.parse()?),
+                other => anyhow::bail!("Unknown history option {}", other),
+            }
+        }
+        Ok(filter)
+    }
+}
+
+struct ActiveFile {
+    date: String,
+    file: File,
+    size: u64,
+}
+
+// 以JSON Lines格式追加交易记录，旧文件压缩为gzip
+pub struct TradeRecorder {
+    config: RecorderConfig,
+    active: Mutex<Option<ActiveFile>>,
+}
+
+impl TradeRecorder {
+    pub fn new(config: RecorderConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)
+            .with_context(|| format!("Unable to create trade record directory {}", config.dir))?;
+        Ok(TradeRecorder {
+            config,
+            active: Mutex::new(None),
+        })
+    }
+
+    // 在独立线程中写入，返回供热路径使用的非阻塞句柄
+    pub fn spawn(self) -> RecordSink {
+        let (sender, mut receiver) = mpsc::channel::<TradeDetails>(self.config.queue_capacity.max(1));
+        tokio::task::spawn_blocking(move || {
+            let mut batch = Vec::with_capacity(MAX_BATCH);
+            while let Some(trade) = receiver.blocking_recv() {
+                batch.push(trade);
+                while batch.len() < MAX_BATCH {
+                    match receiver.try_recv() {
+                        Ok(trade) => batch.push(trade),
+                        Err(_) => break,
+                    }
+                }
+                if let Err(e) = self.write_batch(&batch) {
+                    warn!("Failed to record {} trades: {:?}", batch.len(), e);
+                }
+                batch.clear();
+            }
+        });
+        RecordSink {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn write_batch(&self, trades: &[TradeDetails]) -> Result<()> {
+        let mut lines = Vec::new();
+        for trade in trades {
+            serde_json::to_writer(&mut lines, trade)?;
+            lines.push(b'\n');
+        }
+
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let needs_rotation = match active.as_ref() {
+            Some(current) => current.date != date || current.size + lines.len() as u64 > self.config.max_file_bytes,
+            None => false,
+        };
+        if needs_rotation {
+            if let Some(current) = active.take() {
+                self.archive(&current.date)?;
+            }
+        }
+
+        if active.is_none() {
+            let path = self.active_path(&date);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Unable to open trade record file {}", path.display()))?;
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            *active = Some(ActiveFile { date, file, size });
+        }
+
+        let current = active.as_mut().expect("active record file");
+        current.file.write_all(&lines)?;
+        current.size += lines.len() as u64;
+        Ok(())
+    }
+
+    // 按时间顺序读取所有记录(含已压缩的分段)，合并标签和备注
+    pub fn read_records(&self, filter: &RecordFilter) -> Result<Vec<TradeDetails>> {
+        read_records(Path::new(&self.config.dir), &self.annotations()?, filter)
+    }
+
+    pub fn annotations(&self) -> Result<AnnotationStore> {
+        AnnotationStore::load(Path::new(&self.config.dir))
+    }
+
+    fn active_path(&self, date: &str) -> PathBuf {
+        Path::new(&self.config.dir).join(format!("trades-{}{}", date, ACTIVE_SUFFIX))
+    }
+
+    // 把当前文件改名为带序号的分段，并在后台线程压缩
+    fn archive(&self, date: &str) -> Result<()> {
+        let dir = Path::new(&self.config.dir);
+        let active = self.active_path(date);
+        let (rotated, archived) = (1..)
+            .map(|n| {
+                let stem = format!("trades-{}.{:03}", date, n);
+                (dir.join(format!("{}{}", stem, ACTIVE_SUFFIX)), dir.join(format!("{}{}", stem, ARCHIVE_SUFFIX)))
+            })
+            .find(|(rotated, archived)| !rotated.exists() && !archived.exists())
+            .expect("unbounded segment numbers");
+        fs::rename(&active, &rotated)
+            .with_context(|| format!("Unable to rotate {}", active.display()))?;
+
+        std::thread::spawn(move || {
+            if let Err(e) = compress(&rotated, &archived) {
+                warn!("Failed to compress {}: {:?}", rotated.display(), e);
+            }
+        });
+        Ok(())
+    }
+}
+
+// 交易记录的发送端，队列满时丢弃并计数
+#[derive(Clone)]
+pub struct RecordSink {
+    sender: mpsc::Sender<TradeDetails>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RecordSink {
+    pub fn record(&self, trade: &TradeDetails) {
+        if let Err(e) = self.sender.try_send(trade.clone()) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped == 1 || dropped.is_multiple_of(1000) {
+                warn!("Trade recorder falling behind ({}), {} records dropped so far", e, dropped);
+            }
+        }
+    }
+}
+
+// 先写临时文件再改名，读取方看到.gz时即可忽略同名的未压缩分段
+fn compress(source: &Path, target: &Path) -> Result<()> {
+    let tmp = target.with_extension("gz.tmp");
+    let mut input = File::open(source)?;
+    let mut encoder = GzEncoder::new(File::create(&tmp)?, Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::rename(&tmp, target)?;
+    fs::remove_file(source)?;
+    Ok(())
+}
+
+fn read_records(dir: &Path, annotations: &AnnotationStore, filter: &RecordFilter) -> Result<Vec<TradeDetails>> {
+    // 文件名包含日期和序号，按名称排序即为时间顺序；同一日期下带序号的分段早于当前文件
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Unable to read trade record directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            name.starts_with("trades-") && (name.ends_with(ACTIVE_SUFFIX) || name.ends_with(ARCHIVE_SUFFIX))
+        })
+        .collect();
+    // 压缩进行中时未压缩分段和.gz可能同时存在
+    files.retain(|path| {
+        let name = path.to_string_lossy();
+        !name.ends_with(ACTIVE_SUFFIX) || !Path::new(&format!("{}.gz", name)).exists()
+    });
+    files.sort_by_key(|path| {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        let stem = name.trim_end_matches(ARCHIVE_SUFFIX).trim_end_matches(ACTIVE_SUFFIX).to_string();
+        // "trades-2024-01-01" 排在 "trades-2024-01-01.001" 之后
+        match stem.split_once('.') {
+            Some((date, seq)) => (date.to_string(), seq.to_string()),
+            None => (stem, "~".to_string()),
+        }
+    });
+
+    let mut records = Vec::new();
+    for path in files {
+        let reader: Box<dyn Read> = if path.to_string_lossy().ends_with(ARCHIVE_SUFFIX) {
+            Box::new(GzDecoder::new(File::open(&path)?))
+        } else {
+            Box::new(File::open(&path)?)
+        };
+        for (line_no, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TradeDetails>(&line) {
+                Ok(mut trade) => {
+                    annotations.apply(&mut trade);
+                    if filter.matches(&trade) {
+                        records.push(trade);
+                    }
+                }
+                Err(e) => warn!("Skipping malformed record {}:{}: {}", path.display(), line_no + 1, e),
+            }
+        }
+    }
+
+    // limit取最新的N条
+    if let Some(limit) = filter.limit {
+        let skip = records.len().saturating_sub(limit);
+        records.drain(..skip);
+    }
+    Ok(records)
+}
+
+// 打印记录中的交易，每行一笔
+pub fn print_history(recorder: &TradeRecorder, filter: &RecordFilter) -> Result<()> {
+    let records = recorder.read_records(filter)?;
+    for trade in &records {
+        let time = chrono::DateTime::from_timestamp(trade.timestamp, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        println!("{} {} {} {} -> {} {} ({}){}",
+            time, trade.wallet, trade.amount_in, trade.input_token, trade.amount_out, trade.output_token, trade.signature,
+            trade.skip_reason.as_ref().map(|reason| format!(" skipped: {}", reason)).unwrap_or_default());
+        if let Some(annotation) = &trade.annotation {
+            println!("    tags: [{}]{}", annotation.tags.join(", "),
+                annotation.note.as_ref().map(|note| format!(" note: {}", note)).unwrap_or_default());
+        }
+    }
+    println!("{} trades", records.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+
+    fn trade(signature: &str, timestamp: i64, mint: Pubkey) -> TradeDetails {
+        TradeDetails {
+            signature: signature.to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: spl_token::native_mint::id(),
+            output_token: mint,
+            amount_in: 1,
+            amount_out: 2,
+            price: 0.5,
+            timestamp,
+            token_decimals: None,
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn rotated_segments_are_read_back_in_order() {
+        let dir = std::env::temp_dir().join(format!("recorder_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let recorder = TradeRecorder::new(RecorderConfig {
+            dir: dir.to_string_lossy().to_string(),
+            max_file_bytes: 1,
+            queue_capacity: 1,
+        })
+        .unwrap();
+
+        let mint = Pubkey::new_unique();
+        for (i, signature) in ["a", "b", "c"].iter().enumerate() {
+            recorder.write_batch(&[trade(signature, i as i64, mint)]).unwrap();
+        }
+        recorder.write_batch(&[trade("other", 3, Pubkey::new_unique())]).unwrap();
+        // 等待后台压缩完成
+        let pending = || fs::read_dir(&dir).unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .any(|name| name.matches('.').count() > 1 && name.ends_with(ACTIVE_SUFFIX));
+        for _ in 0..500 {
+            if !pending() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let filter = RecordFilter { mint: Some(mint), ..Default::default() };
+        let signatures: Vec<String> = recorder.read_records(&filter).unwrap()
+            .into_iter().map(|t| t.signature).collect();
+        assert_eq!(signatures, vec!["a", "b", "c"]);
+
+        let latest = recorder.read_records(&RecordFilter { limit: Some(1), ..Default::default() }).unwrap();
+        assert_eq!(latest[0].signature, "other");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
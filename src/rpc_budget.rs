@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// RPC调用预算：令牌桶限速，防止免费节点因突发请求封禁
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcBudgetConfig {
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+    // 允许的突发请求数
+    #[serde(default = "default_burst")]
+    pub burst: f64,
+}
+
+fn default_requests_per_second() -> f64 {
+    10.0
+}
+
+fn default_burst() -> f64 {
+    20.0
+}
+
+impl Default for RpcBudgetConfig {
+    fn default() -> Self {
+        RpcBudgetConfig {
+            requests_per_second: default_requests_per_second(),
+            burst: default_burst(),
+        }
+    }
+}
+
+struct Bucket {
+    config: RpcBudgetConfig,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(config: RpcBudgetConfig) -> Self {
+        Bucket { tokens: config.burst, config, refilled_at: Instant::now() }
+    }
+
+    // 取到令牌返回None，否则返回需要等待的时间
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.config.requests_per_second).min(self.config.burst);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return None;
+        }
+        Some(Duration::from_secs_f64((1.0 - self.tokens) / self.config.requests_per_second.max(0.01)))
+    }
+}
+
+// 每个RPC方法的调用统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RpcMethodUsage {
+    pub calls: u64,
+    // 因超出预算而等待的次数
+    pub throttled: u64,
+}
+
+pub struct RpcBudget {
+    bucket: Mutex<Bucket>,
+    usage: Mutex<BTreeMap<&'static str, RpcMethodUsage>>,
+}
+
+impl Default for RpcBudget {
+    fn default() -> Self {
+        RpcBudget::new(RpcBudgetConfig::default())
+    }
+}
+
+impl RpcBudget {
+    pub fn new(config: RpcBudgetConfig) -> Self {
+        RpcBudget {
+            bucket: Mutex::new(Bucket::new(config)),
+            usage: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn configure(&self, config: RpcBudgetConfig) {
+        *self.bucket.lock().unwrap_or_else(|e| e.into_inner()) = Bucket::new(config);
+    }
+
+    // 每次RPC调用前调用，超出预算时等待到有令牌为止
+    pub async fn acquire(&self, method: &'static str) {
+        let mut throttled = false;
+        loop {
+            let wait = self.bucket.lock().unwrap_or_else(|e| e.into_inner()).try_take(Instant::now());
+            match wait {
+                None => break,
+                Some(wait) => {
+                    throttled = true;
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = usage.entry(method).or_default();
+        entry.calls += 1;
+        if throttled {
+            entry.throttled += 1;
+        }
+    }
+
+    pub fn usage(&self) -> BTreeMap<String, RpcMethodUsage> {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.iter().map(|(method, usage)| (method.to_string(), usage.clone())).collect()
+    }
+
+    pub fn total_calls(&self) -> u64 {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.values().map(|u| u.calls).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_refills_at_rate() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(RpcBudgetConfig { requests_per_second: 2.0, burst: 2.0 });
+        bucket.refilled_at = start;
+
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_none());
+        let wait = bucket.try_take(start).unwrap();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        assert!(bucket.try_take(start + Duration::from_millis(500)).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::bot_state::BotState;
+use crate::ledger::SignatureLedger;
+use crate::types::TradeDetails;
+use crate::watchlist::WatchlistSnapshot;
+
+// 运行时状态快照配置：定时及退出时写入，启动时恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    #[serde(default = "default_file")]
+    pub file: String,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_file() -> String {
+    "state_snapshot.json".to_string()
+}
+
+fn default_interval_secs() -> u64 {
+    60
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    pub taken_at: i64,
+    pub paused: bool,
+    // 从旧到新
+    pub recent_trades: Vec<TradeDetails>,
+    pub watchlist: WatchlistSnapshot,
+    #[serde(default)]
+    pub target_pnl: WatchlistSnapshot,
+    pub skip_counts: BTreeMap<String, u64>,
+    pub failed_attempts: u64,
+    // 去重账本中的签名，从旧到新
+    pub processed_signatures: Vec<String>,
+}
+
+pub fn capture(state: &BotState, ledger: &SignatureLedger) -> RuntimeSnapshot {
+    let mut recent_trades = state.recent_trades(usize::MAX);
+    recent_trades.reverse();
+    RuntimeSnapshot {
+        taken_at: chrono::Utc::now().timestamp(),
+        paused: state.is_paused(),
+        recent_trades,
+        watchlist: state.watchlist().snapshot(),
+        target_pnl: state.target_pnl().snapshot(),
+        skip_counts: state.skip_counts(),
+        failed_attempts: state.failed_attempts(),
+        processed_signatures: ledger.signatures(),
+    }
+}
+
+pub fn restore(snapshot: RuntimeSnapshot, state: &BotState, ledger: &SignatureLedger) {
+    state.set_paused(snapshot.paused);
+    for trade in snapshot.recent_trades {
+        state.push_trade(trade);
+    }
+    state.watchlist().restore(snapshot.watchlist);
+    state.target_pnl().restore(snapshot.target_pnl);
+    state.restore_counters(&snapshot.skip_counts, snapshot.failed_attempts);
+    for signature in &snapshot.processed_signatures {
+        ledger.claim(signature);
+    }
+}
+
+pub fn load(path: &str) -> Result<Option<RuntimeSnapshot>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Unable to read snapshot {}", path)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Invalid snapshot {}", path))
+}
+
+// 先写临时文件再重命名，避免进程中断留下半个文件
+pub async fn save(path: &str, snapshot: &RuntimeSnapshot) -> Result<()> {
+    let tmp = format!("{}.tmp", path);
+    tokio::fs::write(&tmp, serde_json::to_vec(snapshot)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+pub fn spawn(config: SnapshotConfig, state: Arc<BotState>, ledger: Arc<SignatureLedger>) {
+    let period = Duration::from_secs(config.interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = save(&config.file, &capture(&state, &ledger)).await {
+                warn!("Failed to write state snapshot: {:?}", e);
+            }
+        }
+    });
+}
+
+// 启动时恢复，返回是否找到快照
+pub fn restore_from_file(config: &SnapshotConfig, state: &BotState, ledger: &SignatureLedger) -> Result<bool> {
+    let Some(snapshot) = load(&config.file)? else { return Ok(false) };
+    info!("Restoring runtime state from {} (taken at {}): {} trades, {} signatures",
+        config.file, snapshot.taken_at, snapshot.recent_trades.len(), snapshot.processed_signatures.len());
+    restore(snapshot, state, ledger);
+    Ok(true)
+}
+
+// 以字符串形式序列化Pubkey为键的表，JSON对象的键只能是字符串
+pub mod pubkey_map {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    pub fn serialize<V: Serialize, S: Serializer>(map: &HashMap<Pubkey, V>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(map.iter().map(|(key, value)| (key.to_string(), value)))
+    }
+
+    pub fn deserialize<'de, V: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<Pubkey, V>, D::Error> {
+        HashMap::<String, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, value)| Ok((Pubkey::from_str(&key).map_err(D::Error::custom)?, value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, SkipReason, TradeCosts, TradeTimeline};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn snapshot_round_trips_runtime_state() {
+        let state = BotState::new();
+        let ledger = SignatureLedger::in_memory();
+        let wsol = spl_token::native_mint::id();
+        let trade = TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: wsol,
+            output_token: Pubkey::new_unique(),
+            amount_in: 1_000_000_000,
+            amount_out: 5_000,
+            price: 200_000.0,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        };
+        state.set_paused(true);
+        state.push_trade(trade.clone());
+        state.watchlist().record(&trade, None);
+        state.record_skip(&SkipReason::Paused);
+        ledger.claim("sig");
+
+        let json = serde_json::to_string(&capture(&state, &ledger)).unwrap();
+        let (restored_state, restored_ledger) = (BotState::new(), SignatureLedger::in_memory());
+        restore(serde_json::from_str(&json).unwrap(), &restored_state, &restored_ledger);
+
+        assert!(restored_state.is_paused());
+        assert_eq!(restored_state.recent_trades(10).len(), 1);
+        assert_eq!(restored_state.watchlist().summaries()[0].open_positions, 1);
+        assert_eq!(restored_state.skip_counts().get("paused"), Some(&1));
+        assert!(!restored_ledger.claim("sig"));
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use crate::config::Config;
+use wallet_copier::price_oracle::PriceOracle;
+use wallet_copier::rpc_budget::RpcBudget;
+use wallet_copier::snapshot;
+use wallet_copier::types::WSOL_MINT;
+use wallet_copier::wallet_balances::WalletBalances;
+use wallet_copier::watchlist::Watchlist;
+
+// 打印跟单钱包的SOL/WSOL余额和代币持仓
+pub async fn print_status(config: &Config, price_oracle: &PriceOracle) -> Result<()> {
+    let wallet = config.copy_wallet_pubkey()?;
+    let client = RpcClient::new(config.rpc_url.clone());
+    let balances = WalletBalances::fetch(&client, &RpcBudget::new(config.rpc_budget.clone()), &wallet).await?;
+
+    let sol = balances.sol_lamports as f64 / 1_000_000_000.0;
+    let wsol = balances.wsol_lamports() as f64 / 1_000_000_000.0;
+    let sol_usd = price_oracle.sol_usd().await;
+
+    println!("Copy wallet: {}", wallet);
+    match sol_usd {
+        Some(sol_usd) => {
+            println!("SOL:  {:.6} (${:.2})", sol, sol * sol_usd);
+            println!("WSOL: {:.6} (${:.2})", wsol, wsol * sol_usd);
+        }
+        None => {
+            println!("SOL:  {:.6}", sol);
+            println!("WSOL: {:.6}", wsol);
+        }
+    }
+
+    print_target_pnl(config)?;
+
+    let mut positions: Vec<_> = balances.token_accounts.iter()
+        .filter(|(_, b)| b.mint.to_string() != WSOL_MINT && b.amount > 0)
+        .collect();
+    positions.sort_by_key(|(account, _)| account.to_string());
+
+    if positions.is_empty() {
+        println!("No open token positions");
+        return Ok(());
+    }
+
+    println!("Open positions:");
+    for (account, holding) in positions {
+        let amount = holding.ui_amount().unwrap_or(holding.amount as f64);
+        let value = match price_oracle.get_price(&holding.mint.to_string()).await {
+            Some(price) => format!("${:.2}", amount * price.price_usd),
+            None => "n/a".to_string(),
+        };
+        println!("  {} {} [{}] value {} (account {})",
+            amount, holding.mint, holding.program, value, account);
+    }
+
+    Ok(())
+}
+
+// 目标钱包的模拟盈亏来自运行中机器人最近一次写入的快照
+fn print_target_pnl(config: &Config) -> Result<()> {
+    let Some(snapshot_config) = &config.snapshot else { return Ok(()) };
+    let Some(runtime) = snapshot::load(&snapshot_config.file)? else { return Ok(()) };
+    let target = Watchlist::default();
+    target.restore(runtime.target_pnl);
+    if let Some(summary) = target.summaries().into_iter().next() {
+        let age = chrono::Utc::now().timestamp() - runtime.taken_at;
+        println!("Target {} simulated PnL: {:+.4} SOL over {} trades, {} open positions (snapshot {}s ago)",
+            summary.wallet, summary.hypothetical_pnl_sol, summary.trades, summary.open_positions, age);
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+use crate::bot_state::BotState;
+use crate::types::{QuoteMints, SkipReason, TradeDetails, TradeDirection};
+use crate::wallet_balances::WalletBalances;
+
+/// 跟单钱包当前的持仓视图
+pub type Portfolio = WalletBalances;
+
+/// 策略对一笔成交的决定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// 分发给下游
+    Copy,
+    /// 只记录，附带原因
+    Skip(SkipReason),
+}
+
+/// 检测到的成交是否分发给下游，由策略决定；自定义过滤(动量、钱包置信度等)实现此trait即可
+pub trait Strategy: Send + Sync {
+    /// 日志和记录中显示的名称
+    fn name(&self) -> &'static str;
+    /// 在暂停和DEX开关检查之后，对每笔成交调用一次
+    fn decide(&self, trade: &TradeDetails, portfolio: &Portfolio) -> Decision;
+}
+
+/// 按配置中的名称创建策略，未配置时为mirror
+pub fn build(name: Option<&str>, quotes: QuoteMints) -> Result<Arc<dyn Strategy>> {
+    match name.unwrap_or("mirror") {
+        "mirror" => Ok(Arc::new(MirrorStrategy::new(quotes))),
+        other => anyhow::bail!("Unknown strategy {}", other),
+    }
+}
+
+/// 默认策略：全部跟随，数量由下游按max_position_size缩放；卖出只在跟单钱包持有该代币时跟随
+pub struct MirrorStrategy {
+    quotes: QuoteMints,
+}
+
+impl MirrorStrategy {
+    /// quotes决定成交方向，应与监控器使用的一致
+    pub fn new(quotes: QuoteMints) -> Self {
+        MirrorStrategy { quotes }
+    }
+}
+
+impl Strategy for MirrorStrategy {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn decide(&self, trade: &TradeDetails, portfolio: &Portfolio) -> Decision {
+        match trade.direction(&self.quotes) {
+            TradeDirection::Sell | TradeDirection::Swap if portfolio.token_amount(&trade.input_token) == 0 => {
+                Decision::Skip(SkipReason::NoPosition { mint: trade.input_token })
+            }
+            _ => Decision::Copy,
+        }
+    }
+}
+
+/// 动量过滤：目标买入时代币最近几笔成交已涨超过阈值则不跟，避免给拉盘的目标接盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MomentumConfig {
+    /// 参与计算的最近成交笔数(含目标本笔)
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// 窗口内涨幅超过该百分比时不跟买
+    #[serde(default = "default_max_rise_pct")]
+    pub max_rise_pct: f64,
+    /// 样本不足时不做判断，照常交给内层策略
+    #[serde(default = "default_min_samples")]
+    pub min_samples: usize,
+}
+
+fn default_window() -> usize {
+    8
+}
+
+fn default_max_rise_pct() -> f64 {
+    30.0
+}
+
+fn default_min_samples() -> usize {
+    3
+}
+
+/// 在内层策略之前检查买入的动量，价格来自监控到的成交
+pub struct MomentumFilter {
+    inner: Arc<dyn Strategy>,
+    config: MomentumConfig,
+    state: Arc<BotState>,
+    quotes: QuoteMints,
+}
+
+impl MomentumFilter {
+    /// 通过动量检查的成交交给inner决定；state应为监控器的[`GrpcMonitor::state`](crate::GrpcMonitor::state)
+    pub fn new(inner: Arc<dyn Strategy>, config: MomentumConfig, state: Arc<BotState>, quotes: QuoteMints) -> Self {
+        MomentumFilter { inner, config, state, quotes }
+    }
+}
+
+impl Strategy for MomentumFilter {
+    fn name(&self) -> &'static str {
+        "momentum"
+    }
+
+    fn decide(&self, trade: &TradeDetails, portfolio: &Portfolio) -> Decision {
+        if trade.direction(&self.quotes) != TradeDirection::Buy {
+            return self.inner.decide(trade, portfolio);
+        }
+        let mint = trade.output_token;
+        match self.state.swap_prices().price_change(&mint, self.config.window) {
+            Some(change) if change.samples >= self.config.min_samples => {
+                debug!("Momentum for {}: {:+.1}% over {} swaps", mint, change.change_pct, change.samples);
+                if change.change_pct > self.config.max_rise_pct {
+                    return Decision::Skip(SkipReason::Momentum {
+                        mint,
+                        change_pct: change.change_pct.round() as i64,
+                        samples: change.samples,
+                    });
+                }
+            }
+            _ => debug!("Not enough observed swaps of {} to evaluate momentum", mint),
+        }
+        self.inner.decide(trade, portfolio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_program::TokenProgram;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+    use crate::wallet_balances::TokenAccountBalance;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn trade(input: Pubkey, output: Pubkey) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: input,
+            output_token: output,
+            amount_in: 100,
+            amount_out: 100,
+            price: 1.0,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn mirror_skips_sells_without_a_position() {
+        let strategy = MirrorStrategy::new(QuoteMints::default());
+        let wsol = spl_token::native_mint::id();
+        let (held, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut portfolio = Portfolio::default();
+        portfolio.token_accounts.insert(Pubkey::new_unique(), TokenAccountBalance {
+            mint: held,
+            amount: 5,
+            decimals: Some(6),
+            program: TokenProgram::Spl,
+        });
+
+        assert_eq!(strategy.decide(&trade(wsol, other), &portfolio), Decision::Copy);
+        assert_eq!(strategy.decide(&trade(held, wsol), &portfolio), Decision::Copy);
+        assert_eq!(strategy.decide(&trade(other, wsol), &portfolio), Decision::Skip(SkipReason::NoPosition { mint: other }));
+    }
+
+    #[test]
+    fn momentum_filter_skips_buys_after_a_run_up() {
+        let state = Arc::new(BotState::new());
+        let config = MomentumConfig { window: 8, max_rise_pct: 30.0, min_samples: 3 };
+        let inner = Arc::new(MirrorStrategy::new(QuoteMints::default()));
+        let strategy = MomentumFilter::new(inner, config, Arc::clone(&state), QuoteMints::default());
+        let (wsol, mint) = (spl_token::native_mint::id(), Pubkey::new_unique());
+        let portfolio = Portfolio::default();
+
+        let mut buy = trade(wsol, mint);
+        state.swap_prices().observe(&buy);
+        state.swap_prices().observe(&buy);
+        assert_eq!(strategy.decide(&buy, &portfolio), Decision::Copy);
+
+        buy.price = 1.5;
+        state.swap_prices().observe(&buy);
+        assert_eq!(strategy.decide(&buy, &portfolio), Decision::Skip(SkipReason::Momentum { mint, change_pct: 50, samples: 3 }));
+        // 卖出不受动量限制，交给内层策略
+        assert_eq!(strategy.decide(&trade(mint, wsol), &portfolio), Decision::Skip(SkipReason::NoPosition { mint }));
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, RwLock};
+use tracing::warn;
+use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::prelude::SubscribeUpdateTransaction;
+
+// 订阅流兼容性统计：未订阅的更新类型、无法识别的更新(较新proto新增的类型解码后为空)、缺少必要字段的消息
+#[derive(Default)]
+pub struct StreamCompat {
+    provider_version: RwLock<Option<String>>,
+    issues: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl StreamCompat {
+    // 同类问题只在首次出现时警告，之后只计数
+    pub fn record(&self, issue: &'static str) {
+        let mut issues = self.issues.lock().unwrap_or_else(|e| e.into_inner());
+        let count = issues.entry(issue).or_default();
+        *count += 1;
+        if *count == 1 {
+            warn!("gRPC provider compatibility: {} (further occurrences are only counted)", issue);
+        }
+    }
+
+    pub fn issues(&self) -> BTreeMap<String, u64> {
+        let issues = self.issues.lock().unwrap_or_else(|e| e.into_inner());
+        issues.iter().map(|(issue, count)| (issue.to_string(), *count)).collect()
+    }
+
+    // GetVersion返回的版本信息，服务不支持时为None
+    pub fn provider_version(&self) -> Option<String> {
+        self.provider_version.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_provider_version(&self, version: String) {
+        *self.provider_version.write().unwrap_or_else(|e| e.into_inner()) = Some(version);
+    }
+}
+
+// 未订阅却收到的更新类型，None表示属于订阅范围内
+pub fn unexpected_update(update: &UpdateOneof) -> Option<&'static str> {
+    match update {
+        UpdateOneof::Account(_) | UpdateOneof::Slot(_) | UpdateOneof::Transaction(_) | UpdateOneof::Ping(_) => None,
+        UpdateOneof::TransactionStatus(_) => Some("unexpected_transaction_status_update"),
+        UpdateOneof::Block(_) => Some("unexpected_block_update"),
+        UpdateOneof::BlockMeta(_) => Some("unexpected_block_meta_update"),
+        UpdateOneof::Entry(_) => Some("unexpected_entry_update"),
+        UpdateOneof::Pong(_) => Some("unexpected_pong_update"),
+    }
+}
+
+// 交易更新缺少的必要字段
+pub fn missing_transaction_field(update: &SubscribeUpdateTransaction) -> Option<&'static str> {
+    let Some(info) = &update.transaction else { return Some("transaction_missing_info") };
+    if info.signature.is_empty() {
+        return Some("transaction_missing_signature");
+    }
+    let Some(tx) = &info.transaction else { return Some("transaction_missing_body") };
+    if tx.message.is_none() {
+        return Some("transaction_missing_message");
+    }
+    if info.meta.is_none() {
+        return Some("transaction_missing_meta");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::{Message, SubscribeUpdateTransactionInfo, Transaction, TransactionStatusMeta};
+
+    #[test]
+    fn reports_missing_transaction_fields() {
+        let mut update = SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: vec![1; 64],
+                transaction: Some(Transaction { message: Some(Message::default()), ..Default::default() }),
+                meta: Some(TransactionStatusMeta::default()),
+                ..Default::default()
+            }),
+            slot: 1,
+        };
+        assert_eq!(missing_transaction_field(&update), None);
+
+        update.transaction.as_mut().unwrap().meta = None;
+        assert_eq!(missing_transaction_field(&update), Some("transaction_missing_meta"));
+        update.transaction.as_mut().unwrap().signature.clear();
+        assert_eq!(missing_transaction_field(&update), Some("transaction_missing_signature"));
+        update.transaction = None;
+        assert_eq!(missing_transaction_field(&update), Some("transaction_missing_info"));
+    }
+}
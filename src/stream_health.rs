@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// gRPC流健康检查配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamHealthConfig {
+    // 流上的slot落后RPC超过该值视为滞后
+    #[serde(default = "default_max_slot_lag")]
+    pub max_slot_lag: u64,
+    // 超过该秒数没有任何更新视为流已停滞
+    #[serde(default = "default_max_silence_secs")]
+    pub max_silence_secs: u64,
+    // 检查间隔（秒）
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+fn default_max_slot_lag() -> u64 {
+    50
+}
+
+fn default_max_silence_secs() -> u64 {
+    30
+}
+
+fn default_check_interval_secs() -> u64 {
+    10
+}
+
+impl Default for StreamHealthConfig {
+    fn default() -> Self {
+        StreamHealthConfig {
+            max_slot_lag: default_max_slot_lag(),
+            max_silence_secs: default_max_silence_secs(),
+            check_interval_secs: default_check_interval_secs(),
+        }
+    }
+}
+
+// 流不健康的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamProblem {
+    Silent { secs: u64 },
+    Lagging { stream_slot: u64, rpc_slot: u64 },
+}
+
+impl std::fmt::Display for StreamProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamProblem::Silent { secs } => write!(f, "no updates for {}s", secs),
+            StreamProblem::Lagging { stream_slot, rpc_slot } => write!(
+                f,
+                "stream slot {} is {} slots behind RPC slot {}",
+                stream_slot,
+                rpc_slot - stream_slot,
+                rpc_slot
+            ),
+        }
+    }
+}
+
+// 单次连接内的流状态
+pub struct StreamHealth {
+    config: StreamHealthConfig,
+    last_update: Instant,
+    last_slot: Option<u64>,
+}
+
+impl StreamHealth {
+    pub fn new(config: StreamHealthConfig) -> Self {
+        StreamHealth {
+            config,
+            last_update: Instant::now(),
+            last_slot: None,
+        }
+    }
+
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_secs.max(1))
+    }
+
+    pub fn last_slot(&self) -> Option<u64> {
+        self.last_slot
+    }
+
+    // 收到任意更新时调用
+    pub fn record_update(&mut self, slot: Option<u64>) {
+        self.last_update = Instant::now();
+        if let Some(slot) = slot {
+            self.last_slot = Some(self.last_slot.map_or(slot, |s| s.max(slot)));
+        }
+    }
+
+    pub fn check_silence(&self, now: Instant) -> Option<StreamProblem> {
+        let silent = now.saturating_duration_since(self.last_update);
+        if silent.as_secs() >= self.config.max_silence_secs {
+            Some(StreamProblem::Silent { secs: silent.as_secs() })
+        } else {
+            None
+        }
+    }
+
+    pub fn check_lag(&self, rpc_slot: u64) -> Option<StreamProblem> {
+        let stream_slot = self.last_slot?;
+        if rpc_slot > stream_slot && rpc_slot - stream_slot > self.config.max_slot_lag {
+            Some(StreamProblem::Lagging { stream_slot, rpc_slot })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lag_is_measured_against_highest_seen_slot() {
+        let mut health = StreamHealth::new(StreamHealthConfig { max_slot_lag: 10, ..Default::default() });
+        assert_eq!(health.check_lag(1_000), None);
+
+        health.record_update(Some(995));
+        health.record_update(Some(990));
+        assert_eq!(health.check_lag(1_005), None);
+        assert_eq!(
+            health.check_lag(1_006),
+            Some(StreamProblem::Lagging { stream_slot: 995, rpc_slot: 1_006 })
+        );
+    }
+
+    #[test]
+    fn silence_is_reported_after_threshold() {
+        let health = StreamHealth::new(StreamHealthConfig { max_silence_secs: 30, ..Default::default() });
+        let start = health.last_update;
+        assert_eq!(health.check_silence(start + Duration::from_secs(29)), None);
+        assert_eq!(
+            health.check_silence(start + Duration::from_secs(31)),
+            Some(StreamProblem::Silent { secs: 31 })
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::types::TradeDetails;
+
+// 每个代币保留的最近成交价样本数
+const MAX_SAMPLES: usize = 16;
+// 超过该时间的样本不再参与报价
+const MAX_SAMPLE_AGE: Duration = Duration::from_secs(300);
+
+// 由观察到的成交得出的代币价格
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ObservedPrice {
+    // 每个完整代币的SOL价格，取近期样本的中位数
+    pub price_sol: f64,
+    pub decimals: u8,
+    pub samples: usize,
+    pub age_secs: u64,
+}
+
+// 最近若干笔成交的价格变化
+#[derive(Debug, Clone, Copy)]
+pub struct PriceChange {
+    pub change_pct: f64,
+    pub samples: usize,
+}
+
+struct MintSamples {
+    decimals: u8,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+// 按代币滚动记录监控到的SOL计价成交价，为盈亏估算等提供不依赖外部价格API的报价
+#[derive(Default)]
+pub struct SwapPriceCache {
+    mints: Mutex<HashMap<Pubkey, MintSamples>>,
+}
+
+impl SwapPriceCache {
+    // 只记录一侧为WSOL且已知精度的成交
+    pub fn observe(&self, trade: &TradeDetails) {
+        self.observe_at(trade, Instant::now());
+    }
+
+    fn observe_at(&self, trade: &TradeDetails, now: Instant) {
+        let wsol = spl_token::native_mint::id();
+        let (Some(decimals), true) = (trade.token_decimals, trade.price > 0.0) else { return };
+        let token = match (trade.input_token == wsol, trade.output_token == wsol) {
+            (true, false) => trade.output_token,
+            (false, true) => trade.input_token,
+            _ => return,
+        };
+        let price_sol = trade.price * 10f64.powi(decimals as i32) / 1_000_000_000.0;
+
+        let mut mints = self.mints.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = mints.entry(token).or_insert_with(|| MintSamples { decimals, samples: VecDeque::new() });
+        entry.decimals = decimals;
+        if entry.samples.len() == MAX_SAMPLES {
+            entry.samples.pop_front();
+        }
+        entry.samples.push_back((now, price_sol));
+    }
+
+    pub fn price(&self, mint: &Pubkey) -> Option<ObservedPrice> {
+        self.price_at(mint, Instant::now())
+    }
+
+    fn price_at(&self, mint: &Pubkey, now: Instant) -> Option<ObservedPrice> {
+        let mints = self.mints.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = mints.get(mint)?;
+        let mut recent: Vec<f64> = entry.samples.iter()
+            .filter(|(observed_at, _)| now.duration_since(*observed_at) < MAX_SAMPLE_AGE)
+            .map(|(_, price)| *price)
+            .collect();
+        let (latest, _) = entry.samples.back()?;
+        if recent.is_empty() {
+            return None;
+        }
+        recent.sort_by(|a, b| a.total_cmp(b));
+        Some(ObservedPrice {
+            price_sol: recent[recent.len() / 2],
+            decimals: entry.decimals,
+            samples: recent.len(),
+            age_secs: now.duration_since(*latest).as_secs(),
+        })
+    }
+
+    // 最近window笔(未过期)成交中最早一笔到最新一笔的涨跌幅
+    pub fn price_change(&self, mint: &Pubkey, window: usize) -> Option<PriceChange> {
+        self.price_change_at(mint, window, Instant::now())
+    }
+
+    fn price_change_at(&self, mint: &Pubkey, window: usize, now: Instant) -> Option<PriceChange> {
+        let mints = self.mints.lock().unwrap_or_else(|e| e.into_inner());
+        let recent: Vec<f64> = mints.get(mint)?.samples.iter()
+            .filter(|(observed_at, _)| now.duration_since(*observed_at) < MAX_SAMPLE_AGE)
+            .map(|(_, price)| *price)
+            .collect();
+        let recent = &recent[recent.len().saturating_sub(window)..];
+        let (first, last) = (recent.first()?, recent.last()?);
+        Some(PriceChange {
+            change_pct: (last / first - 1.0) * 100.0,
+            samples: recent.len(),
+        })
+    }
+
+    pub fn prices(&self) -> HashMap<String, ObservedPrice> {
+        let mints: Vec<Pubkey> = self.mints.lock().unwrap_or_else(|e| e.into_inner()).keys().copied().collect();
+        mints.into_iter()
+            .filter_map(|mint| Some((mint.to_string(), self.price(&mint)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+
+    fn trade(input: Pubkey, output: Pubkey, price: f64) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet: Pubkey::new_unique(),
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: input,
+            output_token: output,
+            amount_in: 1,
+            amount_out: 1,
+            price,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn quotes_median_of_recent_swaps() {
+        let cache = SwapPriceCache::default();
+        let wsol = spl_token::native_mint::id();
+        let mint = Pubkey::new_unique();
+        let start = Instant::now();
+        // 0.03 lamports/最小单位 = 0.00003 SOL/代币
+        cache.observe_at(&trade(wsol, mint, 0.03), start);
+        cache.observe_at(&trade(mint, wsol, 0.05), start);
+        cache.observe_at(&trade(wsol, mint, 9.0), start);
+        cache.observe_at(&trade(mint, Pubkey::new_unique(), 1.0), start);
+
+        let price = cache.price_at(&mint, start).unwrap();
+        assert_eq!(price.samples, 3);
+        assert!((price.price_sol - 0.00005).abs() < 1e-12);
+
+        // 最近两笔: 0.05 -> 9.0 (WSOL和其他代币互换的样本不计入)
+        let change = cache.price_change_at(&mint, 2, start).unwrap();
+        assert_eq!(change.samples, 2);
+        assert!((change.change_pct - 17_900.0).abs() < 1e-6);
+
+        assert!(cache.price_at(&mint, start + MAX_SAMPLE_AGE).is_none());
+        assert!(cache.price_change_at(&mint, 2, start + MAX_SAMPLE_AGE).is_none());
+    }
+}
@@ -0,0 +1,50 @@
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::fmt;
+use std::str::FromStr;
+
+// spl-token-2022不是直接依赖，程序地址在此声明
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+// 代币所属的Token程序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenProgram {
+    Spl,
+    Token2022,
+}
+
+impl TokenProgram {
+    // 根据程序地址判断代币程序，未知程序返回None
+    pub fn from_program_id(program_id: &str) -> Option<Self> {
+        let program_id = Pubkey::from_str(program_id).ok()?;
+        if program_id == spl_token::id() {
+            Some(TokenProgram::Spl)
+        } else if program_id == TOKEN_2022_PROGRAM_ID {
+            Some(TokenProgram::Token2022)
+        } else {
+            None
+        }
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Spl => spl_token::id(),
+            TokenProgram::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+
+    // 使用对应的Token程序推导关联代币账户(ATA)
+    pub fn associated_token_address(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(wallet, mint, &self.program_id())
+    }
+}
+
+impl fmt::Display for TokenProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenProgram::Spl => write!(f, "SPL Token"),
+            TokenProgram::Token2022 => write!(f, "Token-2022"),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::ExecutableCommand;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::io::stdout;
+use std::sync::Arc;
+use std::time::Duration;
+use wallet_copier::grpc_monitor::GrpcMonitor;
+use wallet_copier::types::WSOL_MINT;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+// 终端仪表盘: q 退出, p 暂停/恢复
+pub async fn run(monitor: Arc<GrpcMonitor>) -> Result<()> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = event_loop(&mut terminal, &monitor).await;
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    result
+}
+
+async fn event_loop(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>, monitor: &GrpcMonitor) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, monitor))?;
+
+        if event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('p') => {
+                        let state = monitor.state();
+                        state.set_paused(!state.is_paused());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        tokio::time::sleep(REFRESH_INTERVAL).await;
+    }
+}
+
+fn draw(frame: &mut Frame, monitor: &GrpcMonitor) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(5)])
+        .split(frame.size());
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+
+    frame.render_widget(status_pane(monitor), top[0]);
+    frame.render_widget(balances_pane(monitor), top[1]);
+    frame.render_widget(trades_pane(monitor, rows[1].height as usize), rows[1]);
+}
+
+fn status_pane(monitor: &GrpcMonitor) -> Paragraph<'static> {
+    let state = monitor.state();
+    let (connection, color) = if state.is_connected() {
+        ("connected", Color::Green)
+    } else {
+        ("disconnected", Color::Red)
+    };
+    let latency = match monitor.latency_summary() {
+        Some(s) => format!("p50 {}µs / p95 {}µs ({} trades)", s.p50.as_micros(), s.p95.as_micros(), s.samples),
+        None => "no trades yet".to_string(),
+    };
+
+    Paragraph::new(vec![
+        Line::styled(format!("Stream: {}{}", connection, if state.is_paused() { " (paused)" } else { "" }), Style::default().fg(color)),
+        Line::from(format!("Latency: {}", latency)),
+        Line::from(format!(
+            "Slot: stream {} / rpc {} | stale reconnects: {}",
+            state.stream_slot(),
+            state.rpc_slot(),
+            state.stale_reconnects()
+        )),
+    ])
+    .block(Block::default().borders(Borders::ALL).title(" Connection "))
+}
+
+fn balances_pane(monitor: &GrpcMonitor) -> Paragraph<'static> {
+    let balances = monitor.copy_wallet_balances();
+    let balances = balances.read().unwrap_or_else(|e| e.into_inner());
+    let positions = balances.token_accounts.values()
+        .filter(|b| b.mint.to_string() != WSOL_MINT && b.amount > 0)
+        .count();
+
+    Paragraph::new(vec![
+        Line::from(format!("SOL {:.6} | WSOL {:.6}",
+            balances.sol_lamports as f64 / 1_000_000_000.0,
+            balances.wsol_lamports() as f64 / 1_000_000_000.0)),
+        Line::from(format!("Token positions: {}", positions)),
+    ])
+    .block(Block::default().borders(Borders::ALL).title(" Copy Wallet "))
+}
+
+fn trades_pane(monitor: &GrpcMonitor, height: usize) -> List<'static> {
+    let items: Vec<ListItem> = monitor.state()
+        .recent_trades(height.saturating_sub(2))
+        .into_iter()
+        .map(|trade| {
+            let time = chrono::DateTime::from_timestamp(trade.timestamp, 0)
+                .map(|t| t.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let value = trade.value_usd.map(|v| format!(" ${:.2}", v)).unwrap_or_default();
+            ListItem::new(format!("{} {} {} {} -> {} {}{}  {}",
+                time, trade.wallet, trade.amount_in, trade.input_token,
+                trade.amount_out, trade.output_token, value, trade.signature))
+        })
+        .collect();
+
+    List::new(items).block(Block::default().borders(Borders::ALL).title(" Target Trades (q quit, p pause) "))
+}
@@ -1,22 +1,296 @@
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
+use std::fmt;
 
+/// Wrapped SOL
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// 从一笔交易中解析出的成交
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeDetails {
     pub signature: String,
+    /// 发起成交的钱包
     pub wallet: Pubkey,
+    /// 成交指令所属的程序ID
     pub dex_program: String,
+    /// 解析出该笔成交的DEX，Inferred表示仅由余额变化推断
+    #[serde(default)]
+    pub dex: DexType,
+    /// 花出的代币
     pub input_token: Pubkey,
+    /// 换回的代币
     pub output_token: Pubkey,
+    /// 花出的数量(最小单位)
     pub amount_in: u64,
+    /// 换回的数量(最小单位)
     pub amount_out: u64,
+    /// 计价代币最小单位/代币最小单位，见`token_decimals`
     pub price: f64,
+    /// 解析时的unix秒
     pub timestamp: i64,
+    /// 非计价一侧代币的精度，price为计价代币最小单位/代币最小单位，需要精度换算
+    /// 代币互换(两侧都不是计价代币)时为输出代币的精度，price为输入/输出最小单位之比
+    #[serde(default)]
+    pub token_decimals: Option<u8>,
+    /// 按交易时的SOL/USD价格折算，无价格数据时为None
+    #[serde(default)]
+    pub price_usd: Option<f64>,
+    /// 成交的美元价值，无价格数据时为None
+    #[serde(default)]
+    pub value_usd: Option<f64>,
+    /// 接收、解析和处理的时间点
+    #[serde(default)]
+    pub timeline: TradeTimeline,
+    /// 目标为该笔交易支付的手续费
+    #[serde(default)]
+    pub costs: TradeCosts,
+    /// 未分发给下游时的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<SkipReason>,
+    /// 用户添加的标签和备注，保存在交易记录目录的annotations.json中，读取记录时合并
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<TradeAnnotation>,
+    /// 卖出数量占目标卖出前持仓的比例，由目标钱包的代币余额得出，跟单按同样比例卖出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sell_fraction: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeAnnotation {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl TradeDetails {
+    /// 花出计价代币为买入，换回计价代币为卖出，两侧都不是(或都是)计价代币为互换
+    pub fn direction(&self, quotes: &QuoteMints) -> TradeDirection {
+        match (quotes.contains(&self.input_token), quotes.contains(&self.output_token)) {
+            (true, false) => TradeDirection::Buy,
+            (false, true) => TradeDirection::Sell,
+            _ => TradeDirection::Swap,
+        }
+    }
+    
+    /// 计价一侧的代币，互换时为None
+    pub fn quote_token(&self, quotes: &QuoteMints) -> Option<Pubkey> {
+        match self.direction(quotes) {
+            TradeDirection::Buy => Some(self.input_token),
+            TradeDirection::Sell => Some(self.output_token),
+            TradeDirection::Swap => None,
+        }
+    }
+}
+
+/// 检测到的成交未分发给下游的原因，记录到交易记录并按code计数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    Paused,
+    DexDisabled { dex: DexType },
+    /// 卖出/互换时跟单钱包没有该代币持仓
+    NoPosition { mint: Pubkey },
+    /// 断线补偿到的交易，配置为只记录
+    RecoveredOnly,
+    /// 添加/移除流动性，不是成交
+    Liquidity { action: LiquidityAction },
+    /// 普通转账或空投
+    Transfer,
+    /// 买入前代币在最近几笔成交中已涨过阈值
+    Momentum { mint: Pubkey, change_pct: i64, samples: usize },
+}
+
+impl SkipReason {
+    pub const CODES: [&'static str; 7] = ["paused", "dex_disabled", "no_position", "recovered_only", "liquidity", "transfer", "momentum"];
+
+    /// 计数和记录使用的稳定名称，见`CODES`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SkipReason::Paused => "paused",
+            SkipReason::DexDisabled { .. } => "dex_disabled",
+            SkipReason::NoPosition { .. } => "no_position",
+            SkipReason::RecoveredOnly => "recovered_only",
+            SkipReason::Liquidity { .. } => "liquidity",
+            SkipReason::Transfer => "transfer",
+            SkipReason::Momentum { .. } => "momentum",
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Paused => write!(f, "processing paused"),
+            SkipReason::DexDisabled { dex } => write!(f, "copying disabled for {}", dex),
+            SkipReason::NoPosition { mint } => write!(f, "no {} position to sell", mint),
+            SkipReason::RecoveredOnly => write!(f, "recovered trades are recorded only"),
+            SkipReason::Liquidity { action } => write!(f, "liquidity {} is not a trade", action),
+            SkipReason::Transfer => write!(f, "token transfers are not trades"),
+            SkipReason::Momentum { mint, change_pct, samples } => write!(f, "{} already up {}% over the last {} swaps", mint, change_pct, samples),
+        }
+    }
 }
 
+/// 解析结果的分类：成交，或余额变化类似成交但不应跟单的操作
+// 绝大多数结果是成交，不为此给每笔成交多一次堆分配
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
+pub enum TradeKind {
+    Swap(TradeDetails),
+    Liquidity(LiquidityEvent),
+    Transfer(TransferEvent),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityAction {
+    Add,
+    Remove,
+}
+
+impl fmt::Display for LiquidityAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiquidityAction::Add => write!(f, "add"),
+            LiquidityAction::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityEvent {
+    pub signature: String,
+    pub wallet: Pubkey,
+    pub dex: DexType,
+    pub action: LiquidityAction,
+    pub pool: Pubkey,
+    /// 钱包在该交易中的代币余额变化(mint, 原始单位)
+    pub token_changes: Vec<(String, i128)>,
+}
+
+/// 不经过DEX的转入/转出，SOL转账的mint为WSOL
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferEvent {
+    pub signature: String,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    /// 无法从余额变化确定对方时为None
+    pub from: Option<Pubkey>,
+    pub to: Option<Pubkey>,
+    pub amount: u64,
+    pub incoming: bool,
+    /// SOL转账的对方在转账前余额为0，即新地址
+    pub new_counterparty: bool,
+}
+
+/// 相对计价代币的成交方向，见[`TradeDetails::direction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeDirection {
+    Buy,
+    Sell,
+    Swap,
+}
+
+impl fmt::Display for TradeDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeDirection::Buy => write!(f, "Buy"),
+            TradeDirection::Sell => write!(f, "Sell"),
+            TradeDirection::Swap => write!(f, "Swap"),
+        }
+    }
+}
+
+/// 计价代币集合，默认WSOL、USDC、USDT
+#[derive(Debug, Clone)]
+pub struct QuoteMints {
+    mints: Vec<Pubkey>,
+}
+
+impl QuoteMints {
+    /// 配置为空时使用默认集合
+    pub fn from_config(mints: &[String]) -> Result<Self, solana_sdk::pubkey::ParsePubkeyError> {
+        if mints.is_empty() {
+            return Ok(QuoteMints::default());
+        }
+        let mints = mints.iter()
+            .map(|m| m.parse())
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+        Ok(QuoteMints { mints })
+    }
+    
+    /// 是否为计价代币
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.mints.contains(mint)
+    }
+}
+
+impl Default for QuoteMints {
+    fn default() -> Self {
+        let mints = [WSOL_MINT, USDC_MINT, USDT_MINT].iter()
+            .filter_map(|m| m.parse().ok())
+            .collect();
+        QuoteMints { mints }
+    }
+}
+
+/// 单笔交易从接收到处理完成(解析+估值)的时间线
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeTimeline {
+    pub slot: u64,
+    pub received_at_ms: i64,
+    pub parsed_at_ms: i64,
+    pub parse_micros: u64,
+    pub processed_micros: u64,
+}
+
+/// 发起交易的成本(lamports)：基础签名费、优先费和Jito小费
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TradeCosts {
+    pub base_fee: u64,
+    pub priority_fee: u64,
+    pub tip: u64,
+}
+
+impl TradeCosts {
+    /// 全部成本之和
+    pub fn total(&self) -> u64 {
+        self.base_fee + self.priority_fee + self.tip
+    }
+}
+
+/// 成交所在的DEX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum DexType {
     Raydium,
     PumpFun,
+    Moonshot,
+    Lifinity,
+    Phoenix,
+    Inferred,
+    #[default]
     Unknown,
+}
+
+impl DexType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DexType::Raydium => "Raydium",
+            DexType::PumpFun => "Pump.fun",
+            DexType::Moonshot => "Moonshot",
+            DexType::Lifinity => "Lifinity",
+            DexType::Phoenix => "Phoenix",
+            DexType::Inferred => "Inferred",
+            DexType::Unknown => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for DexType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::solana_program::program_pack::Pack;
+use spl_token::state::Account as TokenAccount;
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::rpc_budget::RpcBudget;
+use crate::token_program::TokenProgram;
+use crate::types::WSOL_MINT;
+
+#[derive(Debug, Clone)]
+pub struct TokenAccountBalance {
+    pub mint: Pubkey,
+    pub amount: u64,
+    // 账户数据中没有精度，只有RPC初始化时能拿到
+    pub decimals: Option<u8>,
+    pub program: TokenProgram,
+}
+
+impl TokenAccountBalance {
+    pub fn ui_amount(&self) -> Option<f64> {
+        self.decimals.map(|d| self.amount as f64 / 10f64.powi(d as i32))
+    }
+}
+
+// 钱包SOL和代币余额的内存视图，由RPC初始化，之后由gRPC账户更新维护
+#[derive(Debug, Clone, Default)]
+pub struct WalletBalances {
+    pub sol_lamports: u64,
+    pub token_accounts: HashMap<Pubkey, TokenAccountBalance>,
+}
+
+impl WalletBalances {
+    pub async fn fetch(client: &RpcClient, budget: &RpcBudget, wallet: &Pubkey) -> Result<Self> {
+        budget.acquire("getBalance").await;
+        let sol_lamports = client.get_balance(wallet).await
+            .context("Unable to fetch SOL balance")?;
+
+        let mut token_accounts = HashMap::new();
+        for program in [TokenProgram::Spl, TokenProgram::Token2022] {
+            budget.acquire("getTokenAccountsByOwner").await;
+            let accounts = client
+                .get_token_accounts_by_owner(wallet, TokenAccountsFilter::ProgramId(program.program_id()))
+                .await
+                .with_context(|| format!("Unable to fetch {} accounts", program))?;
+
+            for keyed in accounts {
+                // jsonParsed编码: {"parsed": {"info": {"mint": ..., "tokenAmount": {...}}}}
+                let data = serde_json::to_value(&keyed.account.data)?;
+                let info = &data["parsed"]["info"];
+                let (Some(account), Some(mint)) = (
+                    Pubkey::from_str(&keyed.pubkey).ok(),
+                    info["mint"].as_str().and_then(|m| Pubkey::from_str(m).ok()),
+                ) else {
+                    continue;
+                };
+                let amount = info["tokenAmount"]["amount"].as_str()
+                    .and_then(|a| a.parse::<u64>().ok())
+                    .unwrap_or(0);
+                let decimals = info["tokenAmount"]["decimals"].as_u64().map(|d| d as u8);
+
+                token_accounts.insert(account, TokenAccountBalance { mint, amount, decimals, program });
+            }
+        }
+
+        Ok(WalletBalances { sol_lamports, token_accounts })
+    }
+
+    pub fn wsol_lamports(&self) -> u64 {
+        self.token_amount(&Pubkey::from_str(WSOL_MINT).unwrap())
+    }
+
+    pub fn token_amount(&self, mint: &Pubkey) -> u64 {
+        self.token_accounts.values()
+            .filter(|b| b.mint == *mint)
+            .map(|b| b.amount)
+            .sum()
+    }
+
+    // 应用一次账户更新，返回是否影响了该钱包的余额
+    pub fn apply_account_update(&mut self, wallet: &Pubkey, pubkey: &Pubkey, owner: &Pubkey, lamports: u64, data: &[u8]) -> bool {
+        if pubkey == wallet {
+            self.sol_lamports = lamports;
+            return true;
+        }
+
+        let program = match TokenProgram::from_program_id(&owner.to_string()) {
+            Some(program) => program,
+            None => return false,
+        };
+
+        // 账户被关闭
+        if lamports == 0 || data.len() < TokenAccount::LEN {
+            return self.token_accounts.remove(pubkey).is_some();
+        }
+
+        // Token-2022账户的扩展数据在基础布局之后
+        let account = match TokenAccount::unpack_from_slice(&data[..TokenAccount::LEN]) {
+            Ok(account) if account.owner == *wallet => account,
+            _ => return false,
+        };
+
+        let decimals = self.token_accounts.get(pubkey).and_then(|b| b.decimals);
+        self.token_accounts.insert(*pubkey, TokenAccountBalance {
+            mint: account.mint,
+            amount: account.amount,
+            decimals,
+            program,
+        });
+        true
+    }
+}
+
+// 记录每个代币最后一次账户更新所在slot的余额视图，用于判断某笔交易是否已反映在余额中
+#[derive(Debug, Clone, Default)]
+pub struct TrackedBalances {
+    pub balances: WalletBalances,
+    updated_slots: HashMap<Pubkey, u64>,
+}
+
+impl TrackedBalances {
+    pub fn new(balances: WalletBalances) -> Self {
+        TrackedBalances { balances, updated_slots: HashMap::new() }
+    }
+
+    pub fn apply_account_update(&mut self, wallet: &Pubkey, pubkey: &Pubkey, owner: &Pubkey, lamports: u64, data: &[u8], slot: u64) -> bool {
+        // 账户关闭后只能从旧记录得到代币
+        let previous_mint = self.balances.token_accounts.get(pubkey).map(|b| b.mint);
+        if !self.balances.apply_account_update(wallet, pubkey, owner, lamports, data) {
+            return false;
+        }
+        if let Some(mint) = self.balances.token_accounts.get(pubkey).map(|b| b.mint).or(previous_mint) {
+            self.updated_slots.insert(mint, slot);
+        }
+        true
+    }
+
+    // 在slot卖出amount个代币占卖出前持仓的比例；账户更新可能先于交易到达，此时余额已是卖出后的
+    pub fn sell_fraction(&self, mint: &Pubkey, amount: u64, slot: u64) -> Option<f64> {
+        let held = self.balances.token_amount(mint);
+        let before = if self.updated_slots.get(mint).is_some_and(|updated| *updated >= slot) { held + amount } else { held };
+        if before == 0 {
+            return None;
+        }
+        Some((amount as f64 / before as f64).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::state::AccountState;
+
+    fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+        let account = TokenAccount {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        };
+        let mut data = vec![0u8; TokenAccount::LEN];
+        account.pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn tracks_sol_and_token_updates() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let mut balances = WalletBalances::default();
+
+        assert!(balances.apply_account_update(&wallet, &wallet, &Pubkey::default(), 2_000_000_000, &[]));
+        assert_eq!(balances.sol_lamports, 2_000_000_000);
+
+        let data = token_account_data(mint, wallet, 42);
+        assert!(balances.apply_account_update(&wallet, &token_account, &spl_token::id(), 2_039_280, &data));
+        assert_eq!(balances.token_amount(&mint), 42);
+
+        // 账户关闭后移除
+        assert!(balances.apply_account_update(&wallet, &token_account, &spl_token::id(), 0, &[]));
+        assert_eq!(balances.token_amount(&mint), 0);
+    }
+
+    #[test]
+    fn sell_fraction_accounts_for_update_order() {
+        let (wallet, mint, token_account) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut tracked = TrackedBalances::default();
+        tracked.apply_account_update(&wallet, &token_account, &spl_token::id(), 2_039_280, &token_account_data(mint, wallet, 100), 10);
+
+        // 交易先到：余额仍是卖出前的100
+        assert_eq!(tracked.sell_fraction(&mint, 25, 11), Some(0.25));
+        // 账户更新先到：余额已是卖出后的75
+        tracked.apply_account_update(&wallet, &token_account, &spl_token::id(), 2_039_280, &token_account_data(mint, wallet, 75), 11);
+        assert_eq!(tracked.sell_fraction(&mint, 25, 11), Some(0.25));
+        // 全部卖出并关闭账户
+        tracked.apply_account_update(&wallet, &token_account, &spl_token::id(), 0, &[], 12);
+        assert_eq!(tracked.sell_fraction(&mint, 75, 12), Some(1.0));
+        assert_eq!(tracked.sell_fraction(&Pubkey::new_unique(), 1, 12), None);
+    }
+
+    #[test]
+    fn ignores_token_accounts_of_other_owners() {
+        let wallet = Pubkey::new_unique();
+        let data = token_account_data(Pubkey::new_unique(), Pubkey::new_unique(), 1);
+        let mut balances = WalletBalances::default();
+
+        assert!(!balances.apply_account_update(&wallet, &Pubkey::new_unique(), &spl_token::id(), 2_039_280, &data));
+        assert!(balances.token_accounts.is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use crate::swap_prices::SwapPriceCache;
+use crate::types::TradeDetails;
+
+// 观察钱包在某个代币上的持仓(原始单位)，mark_price为lamports/代币最小单位
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedPosition {
+    raw_amount: u64,
+    mark_price: f64,
+}
+
+// 只观察不跟单的钱包，按其成交模拟盈亏
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedWallet {
+    trades: u64,
+    sol_spent: u64,
+    sol_received: u64,
+    #[serde(with = "crate::snapshot::pubkey_map")]
+    positions: HashMap<Pubkey, WatchedPosition>,
+}
+
+// 观察名单的完整状态，用于快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchlistSnapshot {
+    #[serde(with = "crate::snapshot::pubkey_map")]
+    wallets: HashMap<Pubkey, WatchedWallet>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedWalletSummary {
+    pub wallet: String,
+    pub trades: u64,
+    pub sol_spent: f64,
+    pub sol_received: f64,
+    pub open_positions: usize,
+    // 已实现部分加上按最新价格估值的持仓
+    pub hypothetical_pnl_sol: f64,
+}
+
+impl WatchedWallet {
+    fn record(&mut self, trade: &TradeDetails, mark_price: Option<f64>) {
+        self.trades += 1;
+        let wsol = spl_token::native_mint::id();
+
+        if trade.input_token == wsol {
+            self.sol_spent += trade.amount_in;
+        } else {
+            let position = self.positions.entry(trade.input_token).or_default();
+            position.raw_amount = position.raw_amount.saturating_sub(trade.amount_in);
+            if position.raw_amount == 0 {
+                self.positions.remove(&trade.input_token);
+            }
+        }
+
+        if trade.output_token == wsol {
+            self.sol_received += trade.amount_out;
+        } else {
+            let position = self.positions.entry(trade.output_token).or_default();
+            position.raw_amount += trade.amount_out;
+        }
+
+        // 以SOL计价的成交价即为该代币当前的参考价格
+        let token = if trade.input_token == wsol { trade.output_token } else { trade.input_token };
+        let is_sol_quoted = trade.input_token == wsol || trade.output_token == wsol;
+        let mark = mark_price.or(if is_sol_quoted { Some(trade.price) } else { None });
+        if let (Some(mark), Some(position)) = (mark, self.positions.get_mut(&token)) {
+            position.mark_price = mark;
+        }
+    }
+
+    fn hypothetical_pnl_lamports(&self) -> f64 {
+        let holdings: f64 = self.positions.values()
+            .map(|p| p.raw_amount as f64 * p.mark_price)
+            .sum();
+        self.sol_received as f64 - self.sol_spent as f64 + holdings
+    }
+}
+
+// 观察名单：候选钱包的交易只记录并模拟盈亏，从不分发
+#[derive(Default)]
+pub struct Watchlist {
+    wallets: RwLock<HashMap<Pubkey, WatchedWallet>>,
+}
+
+impl Watchlist {
+    pub fn record(&self, trade: &TradeDetails, mark_price: Option<f64>) {
+        let mut wallets = self.wallets.write().unwrap_or_else(|e| e.into_inner());
+        wallets.entry(trade.wallet).or_default().record(trade, mark_price);
+    }
+
+    pub fn snapshot(&self) -> WatchlistSnapshot {
+        let wallets = self.wallets.read().unwrap_or_else(|e| e.into_inner());
+        WatchlistSnapshot { wallets: wallets.clone() }
+    }
+
+    pub fn restore(&self, snapshot: WatchlistSnapshot) {
+        *self.wallets.write().unwrap_or_else(|e| e.into_inner()) = snapshot.wallets;
+    }
+
+    // 有近期成交价的持仓按成交价重新估值
+    pub(crate) fn refresh_marks(&self, prices: &SwapPriceCache) {
+        let mut wallets = self.wallets.write().unwrap_or_else(|e| e.into_inner());
+        for (mint, position) in wallets.values_mut().flat_map(|wallet| wallet.positions.iter_mut()) {
+            if let Some(price) = prices.price(mint) {
+                position.mark_price = mark_from_sol_price(price.price_sol, price.decimals);
+            }
+        }
+    }
+
+    pub fn summaries(&self) -> Vec<WatchedWalletSummary> {
+        let wallets = self.wallets.read().unwrap_or_else(|e| e.into_inner());
+        let mut summaries: Vec<WatchedWalletSummary> = wallets.iter()
+            .map(|(wallet, stats)| WatchedWalletSummary {
+                wallet: wallet.to_string(),
+                trades: stats.trades,
+                sol_spent: stats.sol_spent as f64 / 1_000_000_000.0,
+                sol_received: stats.sol_received as f64 / 1_000_000_000.0,
+                open_positions: stats.positions.len(),
+                hypothetical_pnl_sol: stats.hypothetical_pnl_lamports() / 1_000_000_000.0,
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.hypothetical_pnl_sol.total_cmp(&a.hypothetical_pnl_sol));
+        summaries
+    }
+}
+
+// 代币价格换算成lamports/代币最小单位
+pub fn mark_from_sol_price(price_sol: f64, decimals: u8) -> f64 {
+    price_sol * 1_000_000_000.0 / 10f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradeCosts, TradeTimeline};
+
+    fn trade(wallet: Pubkey, input: Pubkey, output: Pubkey, amount_in: u64, amount_out: u64, price: f64) -> TradeDetails {
+        TradeDetails {
+            signature: "sig".to_string(),
+            wallet,
+            dex_program: "program".to_string(),
+            dex: DexType::Unknown,
+            input_token: input,
+            output_token: output,
+            amount_in,
+            amount_out,
+            price,
+            timestamp: 0,
+            token_decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            timeline: TradeTimeline::default(),
+            costs: TradeCosts::default(),
+            skip_reason: None,
+            annotation: None,
+            sell_fraction: None,
+        }
+    }
+
+    #[test]
+    fn pnl_combines_realized_and_marked_holdings() {
+        let watchlist = Watchlist::default();
+        let wallet = Pubkey::new_unique();
+        let wsol = spl_token::native_mint::id();
+        let mint = Pubkey::new_unique();
+
+        // 1 SOL买入1000单位，卖出一半得到0.75 SOL，成交价1.5M lamports/单位
+        watchlist.record(&trade(wallet, wsol, mint, 1_000_000_000, 1_000, 1_000_000.0), None);
+        watchlist.record(&trade(wallet, mint, wsol, 500, 750_000_000, 1_500_000.0), None);
+
+        let summary = &watchlist.summaries()[0];
+        assert_eq!(summary.trades, 2);
+        assert_eq!(summary.open_positions, 1);
+        // -1 + 0.75 + 500 * 1.5M lamports = 0.5 SOL
+        assert!((summary.hypothetical_pnl_sol - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn refresh_marks_uses_latest_swap_price() {
+        let watchlist = Watchlist::default();
+        let prices = SwapPriceCache::default();
+        let wallet = Pubkey::new_unique();
+        let wsol = spl_token::native_mint::id();
+        let mint = Pubkey::new_unique();
+
+        watchlist.record(&trade(wallet, wsol, mint, 1_000_000_000, 1_000, 1_000_000.0), None);
+        // 其他钱包以3M lamports/单位成交，持仓估值变为3 SOL
+        prices.observe(&trade(Pubkey::new_unique(), wsol, mint, 3_000_000, 1, 3_000_000.0));
+        watchlist.refresh_marks(&prices);
+
+        assert!((watchlist.summaries()[0].hypothetical_pnl_sol - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+use crate::cluster::ClusterCandidate;
+use crate::digest::DigestSummary;
+use crate::explorer::Explorer;
+use crate::parser::attempts::AttemptedTrade;
+use crate::parser::pools::NewPool;
+use crate::types::{TradeDetails, TransferEvent};
+
+// 签名头: hex(HMAC-SHA256(secret, body))
+const SIGNATURE_HEADER: &str = "X-Signature-SHA256";
+// 重试间隔从500ms起翻倍，最多翻倍到约4分钟后不再增长
+const MAX_BACKOFF_DOUBLINGS: u32 = 9;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    // 目标钱包的普通转账/空投也推送
+    #[serde(default)]
+    pub notify_transfers: bool,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+#[derive(Serialize)]
+struct WebhookEvent<'a, T: Serialize> {
+    event: &'a str,
+    data: &'a T,
+    // 区块浏览器中的交易链接
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+}
+
+// 将事件以JSON POST到用户配置的地址，在后台任务中发送并重试
+pub struct WebhookSink {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    explorer: Explorer,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig, explorer: Explorer) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        WebhookSink { config, client, explorer }
+    }
+
+    pub fn send_trade(self: &Arc<Self>, trade: &TradeDetails) {
+        self.send_event("trade_detected", trade, Some(&trade.signature));
+    }
+
+    pub fn send_new_pool(self: &Arc<Self>, pool: &NewPool) {
+        self.send_event("pool_created", pool, Some(&pool.signature));
+    }
+
+    pub fn send_migration(self: &Arc<Self>, pool: &NewPool) {
+        self.send_event("token_migrated", pool, Some(&pool.signature));
+    }
+
+    pub fn send_cluster_candidate(self: &Arc<Self>, candidate: &ClusterCandidate) {
+        self.send_event("cluster_candidate", candidate, Some(&candidate.funding_signature));
+    }
+
+    pub fn send_cluster_trading(self: &Arc<Self>, candidate: &ClusterCandidate, signature: &str) {
+        self.send_event("cluster_trading", candidate, Some(signature));
+    }
+
+    pub fn send_failed_attempt(self: &Arc<Self>, attempt: &AttemptedTrade) {
+        self.send_event("trade_failed", attempt, Some(&attempt.signature));
+    }
+
+    pub fn send_transfer(self: &Arc<Self>, transfer: &TransferEvent) {
+        if self.config.notify_transfers {
+            self.send_event("transfer", transfer, Some(&transfer.signature));
+        }
+    }
+
+    pub fn send_digest(self: &Arc<Self>, summary: &DigestSummary) {
+        self.send_event("digest", summary, None);
+    }
+
+    fn send_event<T: Serialize>(self: &Arc<Self>, event: &str, data: &T, signature: Option<&str>) {
+        let link = signature.map(|signature| self.explorer.tx_url(signature));
+        let body = match serde_json::to_vec(&WebhookEvent { event, data, link }) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook event {}: {:?}", event, e);
+                return;
+            }
+        };
+
+        let sink = Arc::clone(self);
+        let event = event.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = sink.post_with_retries(&body).await {
+                warn!("Webhook delivery of {} failed: {:?}", event, e);
+            }
+        });
+    }
+
+    async fn post_with_retries(&self, body: &[u8]) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.post(body).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    let backoff = retry_backoff(attempt);
+                    debug!("Webhook attempt {} failed ({:?}), retrying in {:?}", attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn post(&self, body: &[u8]) -> Result<()> {
+        let mut request = self.client
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+
+        if let Some(secret) = &self.config.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body));
+        }
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(500 * 2u64.pow(attempt.saturating_sub(1).min(MAX_BACKOFF_DOUBLINGS)))
+}
+
+pub(crate) fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_body_with_hmac_sha256() {
+        // RFC 4231 测试用例2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn retry_backoff_doubles_up_to_a_cap() {
+        assert_eq!(retry_backoff(1), Duration::from_millis(500));
+        assert_eq!(retry_backoff(2), Duration::from_millis(1_000));
+        assert_eq!(retry_backoff(u32::MAX), retry_backoff(MAX_BACKOFF_DOUBLINGS + 1));
+    }
+}